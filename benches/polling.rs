@@ -0,0 +1,110 @@
+//! Measures the time and allocations of polling four gamepads each frame,
+//! with every gamepad's input changing each frame.
+//!
+//! Only a `MockSource` is measured.
+//! The browser sources reuse their snapshots too, but their bindings allocate
+//! when reading the gamepads, so they are not allocation-free.
+//!
+//! Run with `cargo bench --features testing`.
+//! Fails if polling allocates once the event buffers have grown.
+
+extern crate gamepad_web;
+
+use gamepad_web::*;
+
+use std::alloc::{
+    GlobalAlloc,
+    Layout,
+    System,
+};
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+use std::time::Instant;
+
+/// Counts allocations, including reallocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const PADS: usize = 4;
+const WARMUP_FRAMES: usize = 100;
+const FRAMES: usize = 100_000;
+
+/// Move a stick, press a button, and release the previous button, on every gamepad.
+fn change_input(monitor: &mut Monitor<MockSource>, frame: usize) {
+    let source = monitor.source_mut();
+    for pad in 0..PADS {
+        let angle = (frame + pad) as f64 * 0.1;
+        source.set_axis(pad, 0, angle.cos());
+        source.set_axis(pad, 1, angle.sin());
+        source.press(pad, frame % 17);
+        source.release(pad, (frame + 16) % 17);
+        source.bump_timestamp(pad);
+    }
+}
+
+/// Run `poll` once per frame, printing the time and allocations per frame, and return the total allocations.
+fn measure<F: FnMut(&mut Monitor<MockSource>)>(name: &str, mut poll: F) -> usize {
+    let mut monitor = Monitor::with_source(MockSource::new());
+    for pad in 0..PADS {
+        let desc = GamepadDescription::new(pad as i32, format!("pad {}", pad), GamepadMappingType::Standard, 4, 17);
+        monitor.source_mut().connect(desc);
+    }
+
+    for frame in 0..WARMUP_FRAMES {
+        change_input(&mut monitor, frame);
+        poll(&mut monitor);
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for frame in WARMUP_FRAMES..WARMUP_FRAMES + FRAMES {
+        change_input(&mut monitor, frame);
+        poll(&mut monitor);
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    println!("{:>12}: {:8.3} us/frame, {:6.3} allocations/frame",
+        name,
+        elapsed.as_secs_f64() * 1e6 / FRAMES as f64,
+        allocations as f64 / FRAMES as f64);
+    allocations
+}
+
+fn main() {
+    let mut events = Vec::new();
+    let raw = measure("poll_into", |monitor| {
+        events.clear();
+        monitor.poll_into(&mut events);
+    });
+
+    let mut mapped = Vec::new();
+    let mapped = measure("poll_mapped", |monitor| {
+        mapped.clear();
+        monitor.poll_mapped_into(&mut mapped);
+    });
+
+    assert_eq!((raw, mapped), (0, 0), "polling allocated in the steady state");
+}
@@ -4,6 +4,9 @@ extern crate stdweb;
 use gamepad_web::{
     Axis,
     Button,
+    DeadZone,
+    DeadZoneShape,
+    DeadZones,
     MappedEvent,
     Monitor,
 };
@@ -25,20 +28,13 @@ struct State {
     jump_start: Option<Instant>,
 }
 
-fn deadzone(val: f64, zone: f64) -> f64 {
-    let mag = (val.abs() - zone) / (1.0 - zone);
-    if mag < 0.0 {
-        0.0
-    }
-    else {
-        mag * val.signum()
-    }
-}
-
 impl State {
     fn new() -> Self {
+        let mut monitor = Monitor::new();
+        monitor.set_dead_zones(DeadZones::both(DeadZone::new(DeadZoneShape::ScaledRadial, 0.15, 1.0)));
+
         Self {
-            monitor: Monitor::new(),
+            monitor,
             player: document().query_selector("#player").unwrap().unwrap(),
             position: 50.0,
             velocity: 0.0,
@@ -54,7 +50,7 @@ impl State {
                 MappedEvent::ButtonRelease(Button::South) =>
                     self.end_jump(),
                 MappedEvent::Axis(Axis::LeftStickX, x) =>
-                    self.velocity = deadzone(x, 0.15),
+                    self.velocity = x,
                 _ => (),
             }
         }
@@ -0,0 +1,568 @@
+use ::event::MappedEvent;
+use ::mapping::{
+    Axis,
+    Button,
+};
+use ::monitor::Monitor;
+use ::sdl::AxisHalf;
+use ::source::GamepadSource;
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+use std::error::Error;
+use std::fmt::{
+    self,
+    Debug,
+    Display,
+    Formatter,
+};
+use std::mem;
+
+/// An input which can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Binding {
+    /// A button. Its value is the amount it is pressed [0–1].
+    Button(Button),
+    /// An axis. Its value is the axis value [-1–1].
+    Axis(Axis),
+    /// Half of an axis, as a button. Its value is how far the axis moved that way [0–1].
+    HalfAxis(Axis, AxisHalf),
+    /// Two buttons, as a virtual axis. (negative, positive)
+    ///
+    /// Its value is -1.0 while only the negative button is pressed,
+    /// 1.0 while only the positive button is, and 0.0 otherwise.
+    ButtonAxis(Button, Button),
+}
+
+/// An input read from a mapped event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Button(Button),
+    Axis(Axis),
+}
+
+impl Binding {
+
+    fn uses(self, input: Input) -> bool {
+        match (self, input) {
+            (Binding::Button(button), Input::Button(other)) => button == other,
+            (Binding::Axis(axis), Input::Axis(other))
+                | (Binding::HalfAxis(axis, _), Input::Axis(other)) => axis == other,
+            (Binding::ButtonAxis(negative, positive), Input::Button(other)) => negative == other || positive == other,
+            _ => false,
+        }
+    }
+
+    /// Do these bindings read the same input?
+    ///
+    /// Opposite halves of an axis do not overlap.
+    pub fn overlaps(self, other: Binding) -> bool {
+        match (self, other) {
+            (Binding::HalfAxis(axis, half), Binding::HalfAxis(other_axis, other_half)) =>
+                axis == other_axis && half == other_half,
+            (Binding::Button(button), _) => other.uses(Input::Button(button)),
+            (Binding::Axis(axis), _) | (Binding::HalfAxis(axis, _), _) => other.uses(Input::Axis(axis)),
+            (Binding::ButtonAxis(negative, positive), _) =>
+                other.uses(Input::Button(negative)) || other.uses(Input::Button(positive)),
+        }
+    }
+
+    /// Is the bound input active, and what is its value?
+    fn read(self, inputs: &Inputs, threshold: f64) -> (bool, f64) {
+        match self {
+            Binding::Button(button) => {
+                let value = inputs.button_value(button);
+                (inputs.button_active(button, threshold), value)
+            },
+            Binding::Axis(axis) => {
+                let value = inputs.axis(axis);
+                (value.abs() >= threshold, value)
+            },
+            Binding::HalfAxis(axis, half) => {
+                let value = inputs.axis(axis);
+                let value = match half {
+                    AxisHalf::Negative if value < 0.0 => -value,
+                    AxisHalf::Positive if value > 0.0 => value,
+                    _ => 0.0,
+                };
+                (value >= threshold, value)
+            },
+            Binding::ButtonAxis(negative, positive) => {
+                let value = match (inputs.button_active(negative, threshold), inputs.button_active(positive, threshold)) {
+                    (true, false) => -1.0,
+                    (false, true) => 1.0,
+                    _ => 0.0,
+                };
+                (value != 0.0, value)
+            },
+        }
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Binding::Button(button) => write!(f, "{:?}", button),
+            Binding::Axis(axis) => write!(f, "{:?}", axis),
+            Binding::HalfAxis(axis, AxisHalf::Negative) => write!(f, "-{:?}", axis),
+            Binding::HalfAxis(axis, AxisHalf::Positive) => write!(f, "+{:?}", axis),
+            Binding::ButtonAxis(negative, positive) => write!(f, "{:?}/{:?}", negative, positive),
+        }
+    }
+}
+
+/// A change to an action, from an [ActionMap](struct.ActionMap.html).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActionEvent<A> {
+    /// The action became active: a bound button was pressed, or an axis moved past the threshold.
+    Started(A),
+    /// The action is still active. Emitted once per frame by [update()](struct.ActionMap.html#method.update),
+    /// for each action active since the previous frame.
+    Held(A),
+    /// The action is no longer active.
+    Released(A),
+    /// The action's value changed. (action, value [-1–1])
+    Value(A, f64),
+}
+
+/// Two bindings for different actions, which read the same input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BindingConflict<A> {
+    /// The action bound first.
+    pub action: A,
+    /// Its binding.
+    pub binding: Binding,
+    /// The other action.
+    pub other_action: A,
+    /// Its binding, overlapping `binding`.
+    pub other_binding: Binding,
+}
+
+impl<A: Debug> Display for BindingConflict<A> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} for {:?} conflicts with {} for {:?}", self.binding, self.action, self.other_binding, self.other_action)
+    }
+}
+
+impl<A: Debug> Error for BindingConflict<A> {}
+
+#[derive(Debug, Clone, Default)]
+struct Inputs {
+    buttons: BTreeMap<Button, (bool, f64)>,
+    axes: BTreeMap<Axis, f64>,
+}
+
+impl Inputs {
+
+    /// Store the input from this event, and return which input it was.
+    fn apply(&mut self, event: &MappedEvent) -> Option<Input> {
+        match *event {
+            MappedEvent::Axis(axis, value) => {
+                self.axes.insert(axis, value);
+                Some(Input::Axis(axis))
+            },
+            MappedEvent::ButtonValue(button, value) => {
+                self.buttons.entry(button).or_insert((false, 0.0)).1 = value;
+                Some(Input::Button(button))
+            },
+            MappedEvent::ButtonPress(button) | MappedEvent::ButtonRelease(button) => {
+                self.buttons.entry(button).or_insert((false, 0.0)).0 = *event == MappedEvent::ButtonPress(button);
+                Some(Input::Button(button))
+            },
+            MappedEvent::Stick(..) => None,
+        }
+    }
+
+    fn button_value(&self, button: Button) -> f64 {
+        self.buttons.get(&button).map_or(0.0, |button| button.1)
+    }
+
+    /// Buttons reported only by value, such as triggers mapped from axes, are active past the threshold.
+    fn button_active(&self, button: Button, threshold: f64) -> bool {
+        self.buttons.get(&button).map_or(false, |&(pressed, value)| pressed || value >= threshold)
+    }
+
+    fn axis(&self, axis: Axis) -> f64 {
+        self.axes.get(&axis).cloned().unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct ActionState {
+    active: bool,
+    value: f64,
+    /// Was the action active at the end of the previous frame?
+    held: bool,
+}
+
+/// Bindings from buttons and axes to game actions, such as "Jump" or "MoveX".
+///
+/// Actions are any `Copy + Ord` type, usually an enum.
+/// Each action may have several bindings; it is active while any of them is,
+/// and its value is that of the binding moved furthest.
+/// Buttons are active while pressed, and axes once moved past the
+/// [press threshold](#method.set_press_threshold).
+///
+/// The map is updated from [mapped events](enum.MappedEvent.html), all treated as one player's input.
+/// For several players, use a map for each, and feed it from
+/// [Monitor::poll_mapped_for()](struct.Monitor.html#method.poll_mapped_for).
+///
+/// ```no_run
+/// use gamepad_web::*;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// enum Action {
+///     Jump,
+///     MoveX,
+/// }
+///
+/// let mut monitor = Monitor::new();
+/// let mut actions = ActionMap::new();
+/// actions.bind(Action::Jump, Binding::Button(Button::South));
+/// actions.bind(Action::MoveX, Binding::Axis(Axis::LeftStickX));
+/// actions.bind(Action::MoveX, Binding::ButtonAxis(Button::Left, Button::Right));
+///
+/// // each frame
+/// let mut events = vec![];
+/// actions.update(&mut monitor, &mut events);
+/// for event in events {
+///     match event {
+///         ActionEvent::Started(Action::Jump) => (), // jump
+///         ActionEvent::Value(Action::MoveX, x) => (), // move
+///         _ => (),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ActionMap<A> {
+    bindings: Vec<(A, Binding)>,
+    states: BTreeMap<A, ActionState>,
+    inputs: Inputs,
+    threshold: f64,
+    /// Events caused by changing bindings, reported by the next resolve().
+    pending: Vec<ActionEvent<A>>,
+}
+
+impl<A: Copy + Ord> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Copy + Ord> ActionMap<A> {
+
+    /// Create a map without bindings.
+    pub fn new() -> Self {
+        Self {
+            bindings: vec![],
+            states: BTreeMap::new(),
+            inputs: Inputs::default(),
+            threshold: 0.5,
+            pending: vec![],
+        }
+    }
+
+    /// Set how far an axis, or a button reported only by value, must move to become active. 0.5 by default.
+    pub fn set_press_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+
+    /// Add a binding to an action. Does nothing if it is already bound to this action.
+    ///
+    /// The binding is added even if it [conflicts](#method.conflicts) with another action's.
+    pub fn bind(&mut self, action: A, binding: Binding) {
+        if !self.bindings.contains(&(action, binding)) {
+            self.bindings.push((action, binding));
+            self.refresh_pending(action);
+        }
+    }
+
+    /// Add a binding to an action, unless it overlaps a binding of another action.
+    pub fn try_bind(&mut self, action: A, binding: Binding) -> Result<(), BindingConflict<A>> {
+        let conflict = self.bindings.iter()
+            .find(|&&(other_action, other_binding)| other_action != action && other_binding.overlaps(binding));
+        if let Some(&(other_action, other_binding)) = conflict {
+            return Err(BindingConflict {
+                action: other_action,
+                binding: other_binding,
+                other_action: action,
+                other_binding: binding,
+            });
+        }
+        self.bind(action, binding);
+        Ok(())
+    }
+
+    /// Remove a binding from an action.
+    pub fn unbind(&mut self, action: A, binding: Binding) {
+        self.bindings.retain(|&bound| bound != (action, binding));
+        self.refresh_pending(action);
+    }
+
+    /// Remove every binding of an action.
+    pub fn clear(&mut self, action: A) {
+        self.bindings.retain(|&(bound, _)| bound != action);
+        self.refresh_pending(action);
+    }
+
+    /// Replace every binding of an action with this one.
+    pub fn rebind(&mut self, action: A, binding: Binding) {
+        self.bindings.retain(|&(bound, _)| bound != action);
+        self.bind(action, binding);
+    }
+
+    /// The bindings of an action, in the order they were added.
+    pub fn bindings(&self, action: A) -> impl Iterator<Item=Binding> + '_ {
+        self.bindings.iter()
+            .filter(move |&&(bound, _)| bound == action)
+            .map(|&(_, binding)| binding)
+    }
+
+    /// Every binding, as (action, binding), in the order they were added.
+    pub fn all_bindings(&self) -> &[(A, Binding)] {
+        &self.bindings
+    }
+
+    /// Replace every binding, of every action.
+    pub fn set_bindings<I: IntoIterator<Item=(A, Binding)>>(&mut self, bindings: I) {
+        let old = mem::take(&mut self.bindings);
+        for bound in bindings {
+            if !self.bindings.contains(&bound) {
+                self.bindings.push(bound);
+            }
+        }
+
+        let actions: BTreeSet<A> = old.iter()
+            .chain(&self.bindings)
+            .map(|&(action, _)| action)
+            .collect();
+        for action in actions {
+            self.refresh_pending(action);
+        }
+    }
+
+    /// Every pair of bindings, for different actions, which read the same input.
+    pub fn conflicts(&self) -> Vec<BindingConflict<A>> {
+        let mut conflicts = vec![];
+        for (i, &(action, binding)) in self.bindings.iter().enumerate() {
+            for &(other_action, other_binding) in &self.bindings[i + 1..] {
+                if action != other_action && binding.overlaps(other_binding) {
+                    conflicts.push(BindingConflict { action, binding, other_action, other_binding });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Is this action active?
+    pub fn is_active(&self, action: A) -> bool {
+        self.states.get(&action).map_or(false, |state| state.active)
+    }
+
+    /// The value of this action [-1–1], or 0.0 if unknown.
+    pub fn value(&self, action: A) -> f64 {
+        self.states.get(&action).map_or(0.0, |state| state.value)
+    }
+
+    /// Update the actions from a mapped event, and add any changes to `actions`.
+    ///
+    /// A start or release comes before the value change which caused it.
+    /// Changes caused by binding since the last call, such as releasing an unbound action, come first.
+    pub fn resolve(&mut self, event: &MappedEvent, actions: &mut Vec<ActionEvent<A>>) {
+        actions.append(&mut self.pending);
+
+        let input = match self.inputs.apply(event) {
+            Some(input) => input,
+            None => return,
+        };
+        for i in 0..self.bindings.len() {
+            let (action, binding) = self.bindings[i];
+            if binding.uses(input) {
+                self.refresh(action, actions);
+            }
+        }
+    }
+
+    /// Resolve every mapped event in `monitor`, then add a [Held](enum.ActionEvent.html#variant.Held)
+    /// event for each action active since the previous update.
+    pub fn update<S: GamepadSource>(&mut self, monitor: &mut Monitor<S>, actions: &mut Vec<ActionEvent<A>>) {
+        actions.append(&mut self.pending);
+        while let Some(event) = monitor.poll_mapped() {
+            self.resolve(&event, actions);
+        }
+
+        for (&action, state) in self.states.iter_mut() {
+            if state.active && state.held {
+                actions.push(ActionEvent::Held(action));
+            }
+            state.held = state.active;
+        }
+    }
+
+    fn refresh_pending(&mut self, action: A) {
+        let mut pending = mem::take(&mut self.pending);
+        self.refresh(action, &mut pending);
+        self.pending = pending;
+    }
+
+    /// Read the action's bindings, and add any changes to `actions`.
+    fn refresh(&mut self, action: A, actions: &mut Vec<ActionEvent<A>>) {
+        let (active, value) = self.bindings.iter()
+            .filter(|&&(bound, _)| bound == action)
+            .map(|&(_, binding)| binding.read(&self.inputs, self.threshold))
+            .fold((false, 0.0), |(active, value): (bool, f64), (bound_active, bound_value)| {
+                (active || bound_active, if bound_value.abs() > value.abs() { bound_value } else { value })
+            });
+
+        let state = self.states.entry(action).or_default();
+        if active != state.active {
+            state.active = active;
+            actions.push(if active { ActionEvent::Started(action) } else { ActionEvent::Released(action) });
+        }
+        if value.to_bits() != state.value.to_bits() {
+            state.value = value;
+            actions.push(ActionEvent::Value(action, value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::{
+        GamepadDescription,
+        GamepadMappingType,
+    };
+    use ::mapping::Stick;
+    use ::mock::MockSource;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum Action {
+        Jump,
+        Fire,
+        MoveX,
+    }
+
+    fn resolve(map: &mut ActionMap<Action>, events: &[MappedEvent]) -> Vec<ActionEvent<Action>> {
+        let mut actions = vec![];
+        for event in events {
+            map.resolve(event, &mut actions);
+        }
+        actions
+    }
+
+    #[test]
+    fn test_action_map_resolve() {
+        let mut map = ActionMap::new();
+        map.bind(Action::Jump, Binding::Button(Button::South));
+        map.bind(Action::Fire, Binding::HalfAxis(Axis::RightStickY, AxisHalf::Negative));
+        map.bind(Action::MoveX, Binding::Axis(Axis::LeftStickX));
+        map.bind(Action::MoveX, Binding::ButtonAxis(Button::Left, Button::Right));
+
+        assert_eq!(resolve(&mut map, &[
+            MappedEvent::ButtonPress(Button::South),
+            MappedEvent::ButtonValue(Button::South, 1.0),
+            MappedEvent::Axis(Axis::RightStickY, -0.3),
+            MappedEvent::Axis(Axis::RightStickY, -0.8),
+            MappedEvent::Axis(Axis::LeftStickX, 0.25),
+            MappedEvent::ButtonPress(Button::Left),
+        ]), vec![
+            ActionEvent::Started(Action::Jump),
+            ActionEvent::Value(Action::Jump, 1.0),
+            ActionEvent::Value(Action::Fire, 0.3),
+            ActionEvent::Started(Action::Fire),
+            ActionEvent::Value(Action::Fire, 0.8),
+            ActionEvent::Value(Action::MoveX, 0.25),
+            ActionEvent::Started(Action::MoveX),
+            ActionEvent::Value(Action::MoveX, -1.0),
+        ]);
+        assert!(map.is_active(Action::MoveX));
+        assert_eq!(map.value(Action::MoveX), -1.0);
+
+        // the furthest binding wins, and the action stays active while any binding is
+        assert_eq!(resolve(&mut map, &[
+            MappedEvent::ButtonRelease(Button::Left),
+            MappedEvent::Axis(Axis::LeftStickX, 0.0),
+            MappedEvent::ButtonRelease(Button::South),
+            MappedEvent::ButtonValue(Button::South, 0.0),
+            MappedEvent::Axis(Axis::RightStickY, 0.5),
+        ]), vec![
+            ActionEvent::Released(Action::MoveX),
+            ActionEvent::Value(Action::MoveX, 0.25),
+            ActionEvent::Value(Action::MoveX, 0.0),
+            ActionEvent::Released(Action::Jump),
+            ActionEvent::Value(Action::Jump, 0.0),
+            ActionEvent::Released(Action::Fire),
+            ActionEvent::Value(Action::Fire, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_action_map_rebind() {
+        let mut map = ActionMap::new();
+        map.bind(Action::Jump, Binding::Button(Button::South));
+        assert_eq!(resolve(&mut map, &[MappedEvent::ButtonPress(Button::South)]), vec![ActionEvent::Started(Action::Jump)]);
+
+        // the held action is released by the next resolve
+        map.rebind(Action::Jump, Binding::Button(Button::East));
+        assert_eq!(map.bindings(Action::Jump).collect::<Vec<_>>(), vec![Binding::Button(Button::East)]);
+        assert_eq!(resolve(&mut map, &[
+            MappedEvent::ButtonRelease(Button::South),
+            MappedEvent::ButtonPress(Button::East),
+        ]), vec![
+            ActionEvent::Released(Action::Jump),
+            ActionEvent::Started(Action::Jump),
+        ]);
+
+        map.clear(Action::Jump);
+        assert_eq!(map.bindings(Action::Jump).count(), 0);
+        assert_eq!(resolve(&mut map, &[MappedEvent::Stick(Stick::Left, 0.0, 0.0)]), vec![ActionEvent::Released(Action::Jump)]);
+    }
+
+    #[test]
+    fn test_action_map_conflicts() {
+        let mut map = ActionMap::new();
+        map.bind(Action::Jump, Binding::Button(Button::South));
+        map.bind(Action::MoveX, Binding::ButtonAxis(Button::West, Button::East));
+        map.bind(Action::MoveX, Binding::HalfAxis(Axis::LeftStickX, AxisHalf::Negative));
+        assert_eq!(map.conflicts(), vec![]);
+
+        // opposite halves don't conflict
+        assert_eq!(map.try_bind(Action::Fire, Binding::HalfAxis(Axis::LeftStickX, AxisHalf::Positive)), Ok(()));
+        assert_eq!(map.try_bind(Action::Fire, Binding::Button(Button::East)), Err(BindingConflict {
+            action: Action::MoveX,
+            binding: Binding::ButtonAxis(Button::West, Button::East),
+            other_action: Action::Fire,
+            other_binding: Binding::Button(Button::East),
+        }));
+        assert_eq!(map.bindings(Action::Fire).count(), 1);
+
+        map.bind(Action::Fire, Binding::Axis(Axis::LeftStickX));
+        let conflicts = map.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].to_string(), "-LeftStickX for MoveX conflicts with LeftStickX for Fire");
+    }
+
+    #[test]
+    fn test_action_map_update() {
+        let mut monitor = Monitor::with_source(MockSource::new());
+        monitor.source_mut().connect(GamepadDescription::new(0, "pad".into(), GamepadMappingType::Standard, 4, 17));
+        let mut map = ActionMap::new();
+        map.bind(Action::Jump, Binding::Button(Button::South));
+
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().bump_timestamp(0);
+        let mut actions = vec![];
+        map.update(&mut monitor, &mut actions);
+        assert_eq!(actions, vec![ActionEvent::Started(Action::Jump), ActionEvent::Value(Action::Jump, 1.0)]);
+
+        actions.clear();
+        map.update(&mut monitor, &mut actions);
+        assert_eq!(actions, vec![ActionEvent::Held(Action::Jump)]);
+    }
+}
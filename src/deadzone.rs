@@ -0,0 +1,205 @@
+use ::gamepad::GamepadState;
+use ::mapping::{
+    Axis,
+    Mapping,
+};
+
+/// How a [DeadZone](struct.DeadZone.html) treats the two axes of a stick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DeadZoneShape {
+    /// Each axis is zeroed separately, while it is within the dead zone.
+    ///
+    /// Makes it easy to hold a stick straight along one axis, but snaps diagonals toward the axes.
+    Axial,
+    /// Both axes are zeroed while the stick's distance from center is within the dead zone.
+    ///
+    /// Preserves the stick's direction, but values jump from 0.0 to the inner radius when leaving the dead zone.
+    Radial,
+    /// Like `Radial`, but the distance is rescaled to start from 0.0 at the inner radius,
+    /// so movement is smooth when leaving the dead zone.
+    ScaledRadial,
+}
+
+/// Give `magnitude` the sign of `like`, without producing -0.0
+/// (which would differ from 0.0 when comparing states).
+fn signed(magnitude: f64, like: f64) -> f64 {
+    if magnitude == 0.0 {
+        0.0
+    }
+    else {
+        magnitude.copysign(like)
+    }
+}
+
+/// A dead zone, and an outer saturation zone, for an analog stick.
+///
+/// Distances are from the stick's center, where 1.0 is a fully deflected axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeadZone {
+    /// How the two axes are combined.
+    pub shape: DeadZoneShape,
+    /// Input within this distance reads as 0.0.
+    pub inner: f64,
+    /// Input beyond this distance reads as fully deflected (1.0).
+    pub outer: f64,
+}
+
+impl DeadZone {
+
+    /// Create a dead zone.
+    pub fn new(shape: DeadZoneShape, inner: f64, outer: f64) -> Self {
+        Self {
+            shape,
+            inner,
+            outer,
+        }
+    }
+
+    /// A dead zone which does not change any input.
+    pub fn none() -> Self {
+        DeadZone::new(DeadZoneShape::Axial, 0.0, 1.0)
+    }
+
+    /// Rescale a distance from [inner–outer] to [0–1].
+    fn scale(&self, distance: f64) -> f64 {
+        if distance <= self.inner {
+            0.0
+        }
+        else if distance >= self.outer {
+            1.0
+        }
+        else {
+            (distance - self.inner) / (self.outer - self.inner)
+        }
+    }
+
+    /// Zero distances within the inner radius, saturate distances beyond the outer radius.
+    fn clip(&self, distance: f64) -> f64 {
+        if distance <= self.inner {
+            0.0
+        }
+        else if distance >= self.outer {
+            1.0
+        }
+        else {
+            distance
+        }
+    }
+
+    /// Apply this dead zone to a stick position.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.shape {
+            DeadZoneShape::Axial => (
+                signed(self.clip(x.abs()), x),
+                signed(self.clip(y.abs()), y),
+            ),
+            DeadZoneShape::Radial | DeadZoneShape::ScaledRadial => {
+                let distance = x.hypot(y);
+                if distance <= self.inner {
+                    return (0.0, 0.0);
+                }
+                if self.shape == DeadZoneShape::Radial && distance < self.outer {
+                    return (x, y);
+                }
+                let adjusted = self.scale(distance);
+                (signed(x.abs() / distance * adjusted, x), signed(y.abs() / distance * adjusted, y))
+            },
+        }
+    }
+
+    /// Apply this dead zone to a pair of axes in `state`, found through `mapping`.
+    fn apply_to_state<M: Mapping + ?Sized>(&self, state: &mut GamepadState, mapping: &M, x_axis: Axis, y_axis: Axis) {
+        if *self == DeadZone::none() {
+            return;
+        }
+
+        let x_index = mapping.axis_index(x_axis).filter(|&i| i < state.axis_count());
+        let y_index = mapping.axis_index(y_axis).filter(|&i| i < state.axis_count());
+        if let (Some(x_index), Some(y_index)) = (x_index, y_index) {
+            let (x, y) = self.apply(state.axis(x_index), state.axis(y_index));
+            state.set_axis(x_index, x);
+            state.set_axis(y_index, y);
+        }
+    }
+}
+
+impl Default for DeadZone {
+    fn default() -> Self {
+        DeadZone::none()
+    }
+}
+
+/// The dead zones of both sticks on a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeadZones {
+    /// Applied to [LeftStickX](enum.Axis.html#variant.LeftStickX) and [LeftStickY](enum.Axis.html#variant.LeftStickY).
+    pub left_stick: DeadZone,
+    /// Applied to [RightStickX](enum.Axis.html#variant.RightStickX) and [RightStickY](enum.Axis.html#variant.RightStickY).
+    pub right_stick: DeadZone,
+}
+
+impl DeadZones {
+
+    /// Use the same dead zone for both sticks.
+    pub fn both(zone: DeadZone) -> Self {
+        Self {
+            left_stick: zone,
+            right_stick: zone,
+        }
+    }
+
+    /// Apply these dead zones to the stick axes of `state`, found through `mapping`.
+    pub(crate) fn apply_to_state<M: Mapping + ?Sized>(&self, state: &mut GamepadState, mapping: &M) {
+        self.left_stick.apply_to_state(state, mapping, Axis::LeftStickX, Axis::LeftStickY);
+        self.right_stick.apply_to_state(state, mapping, Axis::RightStickX, Axis::RightStickY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: (f64, f64), expected: (f64, f64)) {
+        assert!((actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_dead_zone_none() {
+        let zone = DeadZone::none();
+        assert_eq!(zone.apply(0.0, 0.0), (0.0, 0.0));
+        assert_eq!(zone.apply(0.01, -0.5), (0.01, -0.5));
+        assert_eq!(zone.apply(-1.0, 1.0), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn test_dead_zone_axial() {
+        let zone = DeadZone::new(DeadZoneShape::Axial, 0.2, 0.9);
+        assert_eq!(zone.apply(0.1, -0.1), (0.0, 0.0));
+        assert_eq!(zone.apply(-0.1, 0.0).0.to_bits(), 0.0f64.to_bits());
+        assert_eq!(zone.apply(0.5, -0.1), (0.5, 0.0));
+        assert_eq!(zone.apply(0.1, -0.95), (0.0, -1.0));
+    }
+
+    #[test]
+    fn test_dead_zone_radial() {
+        let zone = DeadZone::new(DeadZoneShape::Radial, 0.2, 0.9);
+        assert_eq!(zone.apply(0.1, -0.1), (0.0, 0.0));
+        // outside the dead zone, small axes are kept
+        assert_eq!(zone.apply(0.5, -0.1), (0.5, -0.1));
+        assert_near(zone.apply(0.0, -0.95), (0.0, -1.0));
+        assert_near(zone.apply(0.8, 0.8), (0.5f64.sqrt(), 0.5f64.sqrt()));
+    }
+
+    #[test]
+    fn test_dead_zone_scaled_radial() {
+        let zone = DeadZone::new(DeadZoneShape::ScaledRadial, 0.2, 0.8);
+        assert_eq!(zone.apply(0.1, -0.1), (0.0, 0.0));
+        assert_near(zone.apply(0.5, 0.0), (0.5, 0.0));
+        assert_near(zone.apply(0.0, -0.35), (0.0, -0.25));
+        assert_near(zone.apply(-0.9, 0.0), (-1.0, 0.0));
+    }
+}
@@ -0,0 +1,837 @@
+use ::gamepad::{
+    GamepadDescription,
+    GamepadMappingType,
+    GamepadState,
+};
+use ::source::GamepadSource;
+
+use std::collections::HashMap;
+use std::fs::{
+    self,
+    File,
+    OpenOptions,
+};
+use std::io::{
+    self,
+    Read,
+};
+use std::mem;
+use std::os::raw::c_long;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use libc;
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const SYN_REPORT: u16 = 0x00;
+const SYN_DROPPED: u16 = 0x03;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+const ABS_CNT: usize = 0x40;
+
+const BTN_JOYSTICK: u16 = 0x120;
+const BTN_SOUTH: u16 = 0x130;
+const BTN_EAST: u16 = 0x131;
+const BTN_NORTH: u16 = 0x133;
+const BTN_WEST: u16 = 0x134;
+const BTN_TL: u16 = 0x136;
+const BTN_TR: u16 = 0x137;
+const BTN_TL2: u16 = 0x138;
+const BTN_TR2: u16 = 0x139;
+const BTN_SELECT: u16 = 0x13a;
+const BTN_START: u16 = 0x13b;
+const BTN_MODE: u16 = 0x13c;
+const BTN_THUMBL: u16 = 0x13d;
+const BTN_THUMBR: u16 = 0x13e;
+const BTN_DPAD_UP: u16 = 0x220;
+const BTN_DPAD_DOWN: u16 = 0x221;
+const BTN_DPAD_LEFT: u16 = 0x222;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+const KEY_CNT: usize = 0x300;
+
+/// Standard gamepad button index of each evdev key code.
+const STANDARD_KEYS: [(u16, usize); 17] = [
+    (BTN_SOUTH, 0),
+    (BTN_EAST, 1),
+    (BTN_WEST, 2),
+    (BTN_NORTH, 3),
+    (BTN_TL, 4),
+    (BTN_TR, 5),
+    (BTN_TL2, 6),
+    (BTN_TR2, 7),
+    (BTN_SELECT, 8),
+    (BTN_START, 9),
+    (BTN_THUMBL, 10),
+    (BTN_THUMBR, 11),
+    (BTN_DPAD_UP, 12),
+    (BTN_DPAD_DOWN, 13),
+    (BTN_DPAD_LEFT, 14),
+    (BTN_DPAD_RIGHT, 15),
+    (BTN_MODE, 16),
+];
+
+/// Analog triggers are reported as pressed beyond this value [0–1].
+const TRIGGER_THRESHOLD: f64 = 0.1;
+
+/// The state and range of an absolute axis, as reported by `EVIOCGABS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsInfo {
+    /// The current (or initial) raw value.
+    pub value: i32,
+    /// The smallest raw value.
+    pub minimum: i32,
+    /// The largest raw value.
+    pub maximum: i32,
+}
+
+/// The identity and capabilities of an evdev device, needed to interpret its events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvdevInfo {
+    /// The device name, as reported by `EVIOCGNAME`.
+    pub name: String,
+    /// The USB (or Bluetooth) vendor ID.
+    pub vendor_id: u16,
+    /// The USB (or Bluetooth) product ID.
+    pub product_id: u16,
+    /// Each `ABS_*` code the device reports, with its range.
+    pub axes: Vec<(u16, AbsInfo)>,
+    /// Each `KEY_*` / `BTN_*` code the device reports.
+    pub keys: Vec<u16>,
+}
+
+impl EvdevInfo {
+
+    /// Does the device report gamepad or joystick buttons?
+    pub fn is_gamepad(&self) -> bool {
+        self.keys.iter().any(|&key| (BTN_JOYSTICK..BTN_JOYSTICK + 0x20).contains(&key))
+    }
+
+    fn has_key(&self, code: u16) -> bool {
+        self.keys.contains(&code)
+    }
+
+    fn has_axis(&self, code: u16) -> bool {
+        self.axes.iter().any(|&(axis, _)| axis == code)
+    }
+}
+
+/// Where an evdev input is stored in our GamepadState.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    /// An axis [-1–1].
+    Axis(usize),
+    /// An analog trigger, stored as a button value [0–1].
+    Trigger(usize),
+    /// A hat axis, stored as two buttons. (negative button, positive button)
+    Hat(usize, usize),
+}
+
+/// The translation from an evdev device's codes to our axis and button indices.
+#[derive(Debug, Clone)]
+struct Layout {
+    mapping: GamepadMappingType,
+    axis_count: usize,
+    button_count: usize,
+    abs: HashMap<u16, (Target, AbsInfo)>,
+    keys: HashMap<u16, usize>,
+}
+
+impl Layout {
+
+    /// Use the standard gamepad layout if the device reports the kernel's gamepad codes,
+    /// otherwise list every axis and key in code order.
+    fn new(info: &EvdevInfo) -> Self {
+        if info.has_key(BTN_SOUTH) && info.has_axis(ABS_X) && info.has_axis(ABS_Y) {
+            Layout::standard(info)
+        }
+        else {
+            Layout::raw(info)
+        }
+    }
+
+    fn standard(info: &EvdevInfo) -> Self {
+        let abs = info.axes.iter()
+            .filter_map(|&(code, range)| {
+                let target = match code {
+                    ABS_X => Target::Axis(0),
+                    ABS_Y => Target::Axis(1),
+                    ABS_RX => Target::Axis(2),
+                    ABS_RY => Target::Axis(3),
+                    ABS_Z => Target::Trigger(6),
+                    ABS_RZ => Target::Trigger(7),
+                    ABS_HAT0X => Target::Hat(14, 15),
+                    ABS_HAT0Y => Target::Hat(12, 13),
+                    _ => return None,
+                };
+                Some((code, (target, range)))
+            })
+            .collect();
+
+        // analog triggers take priority over their digital buttons
+        let keys = STANDARD_KEYS.iter()
+            .filter(|&&(code, _)| info.has_key(code))
+            .filter(|&&(code, _)| !(code == BTN_TL2 && info.has_axis(ABS_Z)))
+            .filter(|&&(code, _)| !(code == BTN_TR2 && info.has_axis(ABS_RZ)))
+            .cloned()
+            .collect();
+
+        Self {
+            mapping: GamepadMappingType::Standard,
+            axis_count: 4,
+            button_count: 17,
+            abs,
+            keys,
+        }
+    }
+
+    fn raw(info: &EvdevInfo) -> Self {
+        let mut axis_codes: Vec<_> = info.axes.clone();
+        axis_codes.sort_by_key(|&(code, _)| code);
+        let mut key_codes = info.keys.clone();
+        key_codes.sort();
+
+        Self {
+            mapping: GamepadMappingType::NoMapping,
+            axis_count: axis_codes.len(),
+            button_count: key_codes.len(),
+            abs: axis_codes.into_iter()
+                .enumerate()
+                .map(|(i, (code, range))| (code, (Target::Axis(i), range)))
+                .collect(),
+            keys: key_codes.into_iter()
+                .enumerate()
+                .map(|(i, code)| (code, i))
+                .collect(),
+        }
+    }
+}
+
+/// Normalize a raw value in `range` to [-1–1].
+fn normalize_axis(value: i32, range: &AbsInfo) -> f64 {
+    if range.maximum <= range.minimum {
+        return 0.0;
+    }
+    let span = f64::from(range.maximum) - f64::from(range.minimum);
+    let normalized = 2.0 * (f64::from(value) - f64::from(range.minimum)) / span - 1.0;
+    normalized.clamp(-1.0, 1.0)
+}
+
+/// Normalize a raw value in `range` to [0–1].
+fn normalize_trigger(value: i32, range: &AbsInfo) -> f64 {
+    if range.maximum <= range.minimum {
+        return 0.0;
+    }
+    let span = f64::from(range.maximum) - f64::from(range.minimum);
+    let normalized = (f64::from(value) - f64::from(range.minimum)) / span;
+    normalized.clamp(0.0, 1.0)
+}
+
+/// The size of the kernel's `struct input_event`: a `struct timeval`, type, code, and value.
+const EVENT_SIZE: usize = 2 * mem::size_of::<c_long>() + 8;
+
+/// One decoded `struct input_event`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct InputEvent {
+    /// Milliseconds, from the event's timeval.
+    time: f64,
+    kind: u16,
+    code: u16,
+    value: i32,
+}
+
+impl InputEvent {
+
+    /// Decode an event, in native byte order.
+    fn parse(bytes: &[u8]) -> Self {
+        const LONG: usize = mem::size_of::<c_long>();
+
+        let long = |at: usize| {
+            let mut buf = [0u8; 8];
+            if cfg!(target_endian = "little") {
+                buf[..LONG].copy_from_slice(&bytes[at..at + LONG]);
+                i64::from_le_bytes(buf)
+            }
+            else {
+                buf[8 - LONG..].copy_from_slice(&bytes[at..at + LONG]);
+                i64::from_be_bytes(buf)
+            }
+        };
+        let u16_at = |at: usize| u16::from_ne_bytes([bytes[at], bytes[at + 1]]);
+
+        let seconds = long(0);
+        let micros = long(LONG);
+        let at = 2 * LONG;
+
+        Self {
+            time: seconds as f64 * 1000.0 + micros as f64 / 1000.0,
+            kind: u16_at(at),
+            code: u16_at(at + 2),
+            value: i32::from_ne_bytes([bytes[at + 4], bytes[at + 5], bytes[at + 6], bytes[at + 7]]),
+        }
+    }
+}
+
+/// An evdev input device, either live (`/dev/input/event*`) or recorded.
+pub struct EvdevDevice {
+    info: EvdevInfo,
+    layout: Layout,
+    input: Box<dyn Read>,
+    /// The opened device, for re-reading its state after dropped events.
+    live: Option<File>,
+    path: Option<PathBuf>,
+    buffer: Vec<u8>,
+    /// Skip events until the next SYN_REPORT, after SYN_DROPPED.
+    dropped: bool,
+    timestamp: f64,
+    /// State being built from events, not yet completed by a SYN_REPORT.
+    axes: Vec<f64>,
+    buttons: Vec<(bool, f64)>,
+    /// State as of the last SYN_REPORT.
+    reported: GamepadState,
+}
+
+impl EvdevDevice {
+
+    fn with_input(info: EvdevInfo, input: Box<dyn Read>, live: Option<File>, path: Option<PathBuf>) -> Self {
+        let layout = Layout::new(&info);
+        let mut device = Self {
+            axes: vec![0.0; layout.axis_count],
+            buttons: vec![(false, 0.0); layout.button_count],
+            reported: GamepadState::new(0.0, vec![], vec![]),
+            info,
+            layout,
+            input,
+            live,
+            path,
+            buffer: vec![],
+            dropped: false,
+            timestamp: 0.0,
+        };
+
+        let initial: Vec<_> = device.info.axes.iter()
+            .map(|&(code, range)| (code, range.value))
+            .collect();
+        for (code, value) in initial {
+            device.apply(EV_ABS, code, value);
+        }
+        device.report();
+        device
+    }
+
+    /// Open a live device, such as `/dev/input/event0`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path.as_ref())?;
+        let info = ioctl::read_info(&file)?;
+        let keys = ioctl::read_pressed_keys(&file)?;
+        let input = Box::new(file.try_clone()?);
+
+        let mut device = EvdevDevice::with_input(info, input, Some(file), Some(path.as_ref().into()));
+        for key in keys {
+            device.apply(EV_KEY, key, 1);
+        }
+        device.report();
+        Ok(device)
+    }
+
+    /// Read a recorded stream of `struct input_event`s, such as a copy of `/dev/input/event0`.
+    ///
+    /// The stream must be in the native `input_event` layout.
+    /// `info` describes the recorded device, including the initial axis values.
+    ///
+    /// Unlike a live device, which reports its latest state,
+    /// a recording advances by one `SYN_REPORT` each time the [EvdevSource](struct.EvdevSource.html) is polled.
+    pub fn from_recording<R: Read + 'static>(info: EvdevInfo, recording: R) -> Self {
+        EvdevDevice::with_input(info, Box::new(recording), None, None)
+    }
+
+    /// The identity and capabilities of this device.
+    pub fn info(&self) -> &EvdevInfo {
+        &self.info
+    }
+
+    /// Describe this device, as connected at `index`.
+    ///
+    /// The name follows Firefox's Linux format: "vendor-product-name", with IDs in hexadecimal.
+    fn description(&self, index: usize) -> GamepadDescription {
+        GamepadDescription::new(
+            index as i32,
+            format!("{:04x}-{:04x}-{}", self.info.vendor_id, self.info.product_id, self.info.name),
+            self.layout.mapping,
+            self.layout.axis_count,
+            self.layout.button_count,
+        )
+    }
+
+    /// Store an EV_KEY or EV_ABS value in the pending state.
+    fn apply(&mut self, kind: u16, code: u16, value: i32) {
+        match kind {
+            EV_KEY => {
+                if let Some(&index) = self.layout.keys.get(&code) {
+                    let pressed = value != 0;
+                    self.buttons[index] = (pressed, if pressed { 1.0 } else { 0.0 });
+                }
+            },
+            EV_ABS => {
+                if let Some(&(target, range)) = self.layout.abs.get(&code) {
+                    match target {
+                        Target::Axis(index) =>
+                            self.axes[index] = normalize_axis(value, &range),
+                        Target::Trigger(index) => {
+                            let amount = normalize_trigger(value, &range);
+                            self.buttons[index] = (amount > TRIGGER_THRESHOLD, amount);
+                        },
+                        Target::Hat(negative, positive) => {
+                            self.buttons[negative] = (value < 0, if value < 0 { 1.0 } else { 0.0 });
+                            self.buttons[positive] = (value > 0, if value > 0 { 1.0 } else { 0.0 });
+                        },
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Publish the pending state.
+    fn report(&mut self) {
+        self.reported = GamepadState::new(self.timestamp, self.axes.clone(), self.buttons.clone());
+    }
+
+    /// Re-read the whole device state, after the kernel dropped events.
+    fn resync(&mut self) -> io::Result<()> {
+        let (axes, keys) = match self.live {
+            Some(ref file) => (ioctl::read_axes(file)?, ioctl::read_pressed_keys(file)?),
+            None => return Ok(()),
+        };
+
+        for (code, range) in axes {
+            self.apply(EV_ABS, code, range.value);
+        }
+        let released: Vec<_> = self.layout.keys.keys().cloned().collect();
+        for code in released {
+            self.apply(EV_KEY, code, 0);
+        }
+        for code in keys {
+            self.apply(EV_KEY, code, 1);
+        }
+        Ok(())
+    }
+
+    /// Handle one event. Returns true at the end of a report.
+    fn handle(&mut self, event: InputEvent) -> io::Result<bool> {
+        match (event.kind, event.code) {
+            (EV_SYN, SYN_REPORT) => {
+                if self.dropped {
+                    self.dropped = false;
+                    self.resync()?;
+                }
+
+                // timestamps must change for the monitor to see a new state
+                self.timestamp = if event.time > self.timestamp { event.time } else { self.timestamp + 0.001 };
+                self.report();
+                Ok(true)
+            },
+            (EV_SYN, SYN_DROPPED) => {
+                self.dropped = true;
+                Ok(false)
+            },
+            (kind, code) => {
+                if !self.dropped {
+                    self.apply(kind, code, event.value);
+                }
+                Ok(false)
+            },
+        }
+    }
+
+    /// Read available events.
+    ///
+    /// Live devices read until no more events are available, keeping only the last report.
+    /// Recordings read through the next SYN_REPORT.
+    fn update(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; EVENT_SIZE * 64];
+        let chunk_size = if self.live.is_some() { chunk.len() } else { EVENT_SIZE };
+
+        loop {
+            while self.buffer.len() >= EVENT_SIZE {
+                let event = InputEvent::parse(&self.buffer[..EVENT_SIZE]);
+                self.buffer.drain(..EVENT_SIZE);
+                if self.handle(event)? && self.live.is_none() {
+                    return Ok(());
+                }
+            }
+
+            match self.input.read(&mut chunk[..chunk_size]) {
+                Ok(0) => return Ok(()),
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Reads gamepads from Linux evdev devices, for native builds.
+///
+/// Requires the `evdev` feature.
+/// Axes are normalized to [-1–1] and buttons to (pressed, [0–1]), just like the browser's.
+/// Devices reporting the kernel's standard gamepad codes (`BTN_SOUTH`, `ABS_X`, ...)
+/// use the [Standard](enum.GamepadMappingType.html#variant.Standard) mapping;
+/// other devices list their axes and buttons in code order, unmapped.
+///
+/// A device which fails to read (usually because it was unplugged) leaves its slot empty.
+///
+/// Like the browser's gamepads, live devices report their latest state at each snapshot:
+/// every `SYN_REPORT` read since the previous snapshot is applied, and only the last is seen.
+/// A button pressed and released between two snapshots is missed.
+/// Recordings instead advance by one `SYN_REPORT` per snapshot, so none are missed.
+#[derive(Default)]
+pub struct EvdevSource {
+    devices: Vec<Option<EvdevDevice>>,
+}
+
+impl EvdevSource {
+
+    /// Create a source with no devices.
+    pub fn new() -> Self {
+        Self {
+            devices: vec![],
+        }
+    }
+
+    /// Open every gamepad in `/dev/input`.
+    pub fn open_all() -> io::Result<Self> {
+        let mut source = EvdevSource::new();
+        source.scan()?;
+        Ok(source)
+    }
+
+    /// Open any gamepads in `/dev/input` which are not open yet, such as newly plugged ones.
+    ///
+    /// Devices we may not read (for lack of permission) are skipped.
+    pub fn scan(&mut self) -> io::Result<()> {
+        let mut paths: Vec<PathBuf> = fs::read_dir("/dev/input")?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with("event")))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let is_open = self.devices.iter()
+                .filter_map(|device| device.as_ref())
+                .any(|device| device.path.as_ref() == Some(&path));
+            if is_open {
+                continue;
+            }
+
+            if let Ok(device) = EvdevDevice::open(&path) {
+                if device.info.is_gamepad() {
+                    self.add_device(device);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a device in the first empty slot, returning its gamepad index.
+    pub fn add_device(&mut self, device: EvdevDevice) -> usize {
+        match self.devices.iter().position(|device| device.is_none()) {
+            Some(index) => {
+                self.devices[index] = Some(device);
+                index
+            },
+            None => {
+                self.devices.push(Some(device));
+                self.devices.len() - 1
+            },
+        }
+    }
+}
+
+impl GamepadSource for EvdevSource {
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>> {
+        for slot in self.devices.iter_mut() {
+            let failed = match *slot {
+                Some(ref mut device) => device.update().is_err(),
+                None => false,
+            };
+            if failed {
+                *slot = None;
+            }
+        }
+
+        self.devices.iter()
+            .enumerate()
+            .map(|(i, device)| device.as_ref().map(|device| (device.description(i), device.reported.clone())))
+            .collect()
+    }
+}
+
+/// evdev ioctl requests, from `linux/input.h`.
+mod ioctl {
+    use super::{
+        AbsInfo,
+        EvdevInfo,
+        ABS_CNT,
+        EV_ABS,
+        EV_KEY,
+        KEY_CNT,
+    };
+
+    use std::fs::File;
+    use std::io;
+    use std::mem;
+    use std::os::unix::io::AsRawFd;
+
+    use libc;
+
+    const IOC_READ: u64 = 2;
+
+    /// Linux's `_IOC(_IOC_READ, 'E', nr, size)`.
+    fn request(nr: u64, size: usize) -> u64 {
+        (IOC_READ << 30) | ((size as u64) << 16) | (u64::from(b'E') << 8) | nr
+    }
+
+    fn read<T>(file: &File, nr: u64, buf: &mut T) -> io::Result<()> {
+        let size = mem::size_of::<T>();
+        let result = unsafe {
+            libc::ioctl(file.as_raw_fd(), request(nr, size) as _, buf as *mut T)
+        };
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    fn bit(bits: &[u8], index: usize) -> bool {
+        bits[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    /// EVIOCGBIT: the codes an event type supports.
+    fn supported(file: &File, kind: u16, count: usize) -> io::Result<Vec<u16>> {
+        let mut bits = [0u8; super::KEY_CNT / 8];
+        let size = (count + 7) / 8;
+        let result = unsafe {
+            libc::ioctl(file.as_raw_fd(), request(0x20 + u64::from(kind), size) as _, bits.as_mut_ptr())
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((0..count).filter(|&code| bit(&bits, code)).map(|code| code as u16).collect())
+    }
+
+    /// EVIOCGABS: the state and range of each supported axis.
+    pub fn read_axes(file: &File) -> io::Result<Vec<(u16, AbsInfo)>> {
+        supported(file, EV_ABS, ABS_CNT)?
+            .into_iter()
+            .map(|code| {
+                // struct input_absinfo: value, minimum, maximum, fuzz, flat, resolution
+                let mut absinfo = [0i32; 6];
+                read(file, 0x40 + u64::from(code), &mut absinfo)?;
+                Ok((code, AbsInfo {
+                    value: absinfo[0],
+                    minimum: absinfo[1],
+                    maximum: absinfo[2],
+                }))
+            })
+            .collect()
+    }
+
+    /// EVIOCGKEY: the keys currently pressed.
+    pub fn read_pressed_keys(file: &File) -> io::Result<Vec<u16>> {
+        let mut bits = [0u8; KEY_CNT / 8];
+        read(file, 0x18, &mut bits)?;
+        Ok((0..KEY_CNT).filter(|&code| bit(&bits, code)).map(|code| code as u16).collect())
+    }
+
+    /// EVIOCGNAME, EVIOCGID, and the supported axes and keys.
+    pub fn read_info(file: &File) -> io::Result<EvdevInfo> {
+        let mut name = [0u8; 256];
+        read(file, 0x06, &mut name)?;
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+
+        // struct input_id: bustype, vendor, product, version
+        let mut id = [0u16; 4];
+        read(file, 0x02, &mut id)?;
+
+        Ok(EvdevInfo {
+            name: String::from_utf8_lossy(&name[..len]).into_owned(),
+            vendor_id: id[1],
+            product_id: id[2],
+            axes: read_axes(file)?,
+            keys: supported(file, EV_KEY, KEY_CNT)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::event::EventData;
+    use ::monitor::Monitor;
+
+    use std::io::Cursor;
+
+    /// Encode an event, as the kernel would.
+    fn event(time_ms: i64, kind: u16, code: u16, value: i32) -> Vec<u8> {
+        const LONG: usize = mem::size_of::<c_long>();
+        let seconds = time_ms / 1000;
+        let micros = (time_ms % 1000) * 1000;
+
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&(seconds as c_long).to_ne_bytes()[..LONG]);
+        bytes.extend_from_slice(&(micros as c_long).to_ne_bytes()[..LONG]);
+        bytes.extend_from_slice(&kind.to_ne_bytes());
+        bytes.extend_from_slice(&code.to_ne_bytes());
+        bytes.extend_from_slice(&value.to_ne_bytes());
+        bytes
+    }
+
+    fn xbox_info() -> EvdevInfo {
+        let stick = AbsInfo { value: 0, minimum: -32768, maximum: 32767 };
+        let trigger = AbsInfo { value: 0, minimum: 0, maximum: 255 };
+        let hat = AbsInfo { value: 0, minimum: -1, maximum: 1 };
+        EvdevInfo {
+            name: "Microsoft X-Box 360 pad".into(),
+            vendor_id: 0x045e,
+            product_id: 0x028e,
+            axes: vec![
+                (ABS_X, stick),
+                (ABS_Y, stick),
+                (ABS_Z, trigger),
+                (ABS_RX, stick),
+                (ABS_RY, stick),
+                (ABS_RZ, trigger),
+                (ABS_HAT0X, hat),
+                (ABS_HAT0Y, hat),
+            ],
+            keys: vec![BTN_SOUTH, BTN_EAST, BTN_NORTH, BTN_WEST, BTN_TL, BTN_TR,
+                BTN_SELECT, BTN_START, BTN_MODE, BTN_THUMBL, BTN_THUMBR],
+        }
+    }
+
+    fn drain(monitor: &mut Monitor<EvdevSource>) -> Vec<EventData> {
+        let mut events = vec![];
+        while let Some(event) = monitor.poll() {
+            events.push(event.data);
+        }
+        events
+    }
+
+    #[test]
+    fn test_normalize() {
+        let range = AbsInfo { value: 0, minimum: 0, maximum: 255 };
+        assert_eq!(normalize_axis(0, &range), -1.0);
+        assert_eq!(normalize_axis(255, &range), 1.0);
+        assert_eq!(normalize_axis(300, &range), 1.0);
+        assert_eq!(normalize_trigger(0, &range), 0.0);
+        assert_eq!(normalize_trigger(255, &range), 1.0);
+
+        let empty = AbsInfo { value: 0, minimum: 0, maximum: 0 };
+        assert_eq!(normalize_axis(10, &empty), 0.0);
+    }
+
+    #[test]
+    fn test_evdev_recording() {
+        let mut recording = vec![];
+        recording.extend(event(1000, EV_KEY, BTN_SOUTH, 1));
+        recording.extend(event(1000, EV_ABS, ABS_X, 32767));
+        recording.extend(event(1000, EV_SYN, SYN_REPORT, 0));
+        recording.extend(event(1016, EV_KEY, BTN_SOUTH, 0));
+        recording.extend(event(1016, EV_ABS, ABS_RZ, 255));
+        recording.extend(event(1016, EV_ABS, ABS_HAT0Y, -1));
+        recording.extend(event(1016, EV_SYN, SYN_REPORT, 0));
+        // events after SYN_DROPPED are discarded, through the next report
+        recording.extend(event(1032, EV_SYN, SYN_DROPPED, 0));
+        recording.extend(event(1032, EV_KEY, BTN_EAST, 1));
+        recording.extend(event(1032, EV_SYN, SYN_REPORT, 0));
+
+        let mut source = EvdevSource::new();
+        source.add_device(EvdevDevice::from_recording(xbox_info(), Cursor::new(recording)));
+        let mut monitor = Monitor::with_source(source);
+
+        // each report is a separate state, so brief presses are not lost.
+        // the first state includes the initial axis values
+        let rest = normalize_axis(0, &xbox_info().axes[0].1);
+        assert_eq!(drain(&mut monitor), vec![
+            EventData::Connected,
+            EventData::Axis(0, 1.0),
+            EventData::Axis(1, rest),
+            EventData::Axis(2, rest),
+            EventData::Axis(3, rest),
+            EventData::Button(0, true),
+            EventData::ButtonValue(0, 1.0),
+            EventData::Button(0, false),
+            EventData::ButtonValue(0, 0.0),
+            EventData::Button(7, true),
+            EventData::ButtonValue(7, 1.0),
+            EventData::Button(12, true),
+            EventData::ButtonValue(12, 1.0),
+        ]);
+        let desc = monitor.pad_description(0).unwrap();
+        assert_eq!(desc.name, "045e-028e-Microsoft X-Box 360 pad");
+        assert_eq!(desc.mapping, GamepadMappingType::Standard);
+        // the dropped report changed nothing
+        assert_eq!(monitor.pad_state(0).unwrap().timestamp(), 1032.0);
+        assert!(!monitor.pad_state(0).unwrap().button_pressed(1));
+
+        assert_eq!(drain(&mut monitor), vec![]);
+        assert!(monitor.pad_description(0).is_some());
+    }
+
+    #[test]
+    fn test_evdev_raw_layout() {
+        let info = EvdevInfo {
+            name: "Joystick".into(),
+            vendor_id: 0x1234,
+            product_id: 0xabcd,
+            axes: vec![
+                (ABS_Y, AbsInfo { value: 128, minimum: 0, maximum: 255 }),
+                (ABS_X, AbsInfo { value: 0, minimum: 0, maximum: 255 }),
+            ],
+            keys: vec![BTN_JOYSTICK + 1, BTN_JOYSTICK],
+        };
+        assert!(info.is_gamepad());
+
+        let mut recording = vec![];
+        recording.extend(event(5, EV_KEY, BTN_JOYSTICK + 1, 1));
+        recording.extend(event(5, EV_SYN, SYN_REPORT, 0));
+
+        let mut source = EvdevSource::new();
+        source.add_device(EvdevDevice::from_recording(info, Cursor::new(recording)));
+        let mut monitor = Monitor::with_source(source);
+
+        // axes and keys are listed in code order
+        assert_eq!(drain(&mut monitor), vec![
+            EventData::Connected,
+            EventData::Axis(0, -1.0),
+            EventData::Axis(1, normalize_axis(128, &AbsInfo { value: 0, minimum: 0, maximum: 255 })),
+            EventData::Button(1, true),
+            EventData::ButtonValue(1, 1.0),
+        ]);
+        let desc = monitor.pad_description(0).unwrap();
+        assert_eq!(desc.mapping, GamepadMappingType::NoMapping);
+        assert_eq!((desc.axis_count, desc.button_count), (2, 2));
+    }
+}
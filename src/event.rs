@@ -6,7 +6,9 @@ use ::mapping::{
     Axis,
     Button,
     Mapping,
+    Stick,
 };
+use ::table::map_bindings;
 
 use std::fmt::{
     self,
@@ -17,11 +19,26 @@ use std::rc::Rc;
 
 /// The data associated with an event, not including the gamepad itself.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EventData {
     /// A gamepad was connected.
     Connected,
     /// A gamepad was disconnected.
     Disconnected,
+    /// A connected gamepad changed its number of axes or buttons, or its mapping,
+    /// as when its driver reinitializes.
+    ///
+    /// The event's [gamepad](struct.Event.html#structfield.gamepad) is the new description.
+    /// Inputs it no longer has are released first; if the mapping changed, every input is.
+    Reconfigured,
+    /// The page lost focus, or was hidden. Emitted for each connected gamepad, after releasing its input.
+    ///
+    /// No input events are emitted until focus returns.
+    FocusLost,
+    /// The page regained focus. Emitted for each connected gamepad.
+    ///
+    /// Input held at this point is not reported as pressed.
+    FocusGained,
     /// An axis was moved. (axis index, value [-1–1])
     Axis(usize, f64),
     /// A button was pressed or released. (button index, pressed)
@@ -46,11 +63,23 @@ pub struct Event {
     /// A description of the gamepad which emitted this event.
     pub gamepad: Rc<GamepadDescription>,
     /// The event type and values.
-    pub data: EventData
+    pub data: EventData,
+    /// The [timestamp](struct.GamepadState.html#method.timestamp) of the gamepad state
+    /// in which this event was seen.
+    ///
+    /// A disconnection has the timestamp of the gamepad's last state.
+    pub timestamp: f64,
+    /// The position of this event among all events from its [Monitor](struct.Monitor.html),
+    /// including mapped events.
+    ///
+    /// Increases with each event, though not necessarily by 1,
+    /// so events from different gamepads can be merged in order.
+    pub sequence: u64,
 }
 
 /// An input event, mapped to one of the standard buttons or axes.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MappedEvent {
     /// An axis was moved. [-1–1]
     Axis(Axis, f64),
@@ -60,40 +89,112 @@ pub enum MappedEvent {
     ButtonPress(Button),
     /// A button was released.
     ButtonRelease(Button),
+    /// A stick was moved. (stick, x [-1–1], y [-1–1])
+    ///
+    /// Emitted once per stick per update, after the stick's [Axis](#variant.Axis) events,
+    /// so both axes are seen together.
+    /// Only [Monitor::poll_mapped()](struct.Monitor.html#method.poll_mapped) emits this;
+    /// [Event::map()](struct.Event.html#method.map) maps one axis at a time.
+    Stick(Stick, f64, f64),
+}
+
+/// A [MappedEvent](enum.MappedEvent.html), with the gamepad and time it came from.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PadMappedEvent {
+    /// The [index](struct.GamepadDescription.html#structfield.index) of the gamepad which emitted this event.
+    pub pad: usize,
+    /// The mapped input.
+    pub event: MappedEvent,
+    /// The [timestamp](struct.Event.html#structfield.timestamp) of the event.
+    pub timestamp: f64,
+    /// The [sequence number](struct.Event.html#structfield.sequence) of the event.
+    pub sequence: u64,
+}
+
+/// A [MappedEvent](enum.MappedEvent.html), with the player it belongs to.
+///
+/// Players are chosen by the [Monitor's player filter](struct.Monitor.html#method.set_player_filter).
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayerMappedEvent {
+    /// The player number.
+    pub player: usize,
+    /// The mapped input.
+    pub event: MappedEvent,
 }
 
 impl Event {
-    pub(crate) fn new(gamepad: Rc<GamepadDescription>, data: EventData) -> Self {
+    pub(crate) fn new(gamepad: Rc<GamepadDescription>, data: EventData, timestamp: f64, sequence: u64) -> Self {
         Self {
             gamepad,
             data,
+            timestamp,
+            sequence,
         }
     }
 
+    /// Like [map()](#method.map), but keep the gamepad index, timestamp, and sequence number.
+    pub fn map_with_pad(&self) -> Option<PadMappedEvent> {
+        self.map().map(|event| PadMappedEvent {
+            pad: self.gamepad.index as usize,
+            event,
+            timestamp: self.timestamp,
+            sequence: self.sequence,
+        })
+    }
+
     /// Convert this raw event to a standard input event.
     ///
     /// The button-index or axis-index is mapped to a [Button](enum.Button.html) or [Axis](enum.Axis.html).
+    /// Gamepads with a [mapping table](struct.GamepadDescription.html#structfield.mapping_table)
+    /// are mapped through it.
+    /// Gamepads without a standard mapping are mapped through their
+    /// [SDL mapping](struct.GamepadDescription.html#structfield.sdl_mapping), if found,
+    /// in the same way.
     /// Returns `None` if the gamepad mapping is unknown,
     /// or the event is a non-input event (i.e. connect or disconnect).
+    ///
+    /// These mappings may map one raw input to several events, such as a hat switch
+    /// driving the directional pad; this returns the first, and [map_into()](#method.map_into) all of them.
+    /// Without the axis' previous value, every direction it drives is mapped, released or not;
+    /// [Monitor::poll_mapped()](struct.Monitor.html#method.poll_mapped) only emits the directions which changed.
     pub fn map(&self) -> Option<MappedEvent> {
-        match self.data {
+        let mut first = None;
+        self.map_each(|event| { first.get_or_insert(event); });
+        first
+    }
+
+    /// Like [map()](#method.map), but append every standard input event to `events`.
+    pub fn map_into(&self, events: &mut Vec<MappedEvent>) {
+        self.map_each(|event| events.push(event));
+    }
+
+    /// Pass each standard input event to `f`.
+    pub(crate) fn map_each<F: FnMut(MappedEvent)>(&self, f: F) {
+        self.map_each_since(None, f);
+    }
+
+    /// Like [map_each()](#method.map_each), but given the axis' value before this event, if known,
+    /// so only the standard inputs it changed are passed to `f`.
+    pub(crate) fn map_each_since<F: FnMut(MappedEvent)>(&self, previous: Option<f64>, mut f: F) {
+        if let Some(bindings) = self.gamepad.bindings() {
+            return map_bindings(bindings, &self.data, previous, f);
+        }
+
+        let mapped = match self.data {
             EventData::Axis(i, val) =>
-                self.gamepad.mapping.map_axis(i).map(|a|
-                    MappedEvent::Axis(a, val)
-                ),
+                self.gamepad.map_axis(i).map(|a| MappedEvent::Axis(a, val)),
             EventData::Button(i, true) =>
-                self.gamepad.mapping.map_button(i).map(|b|
-                    MappedEvent::ButtonPress(b)
-                ),
+                self.gamepad.map_button(i).map(MappedEvent::ButtonPress),
             EventData::Button(i, false) =>
-                self.gamepad.mapping.map_button(i).map(|b|
-                    MappedEvent::ButtonRelease(b)
-                ),
+                self.gamepad.map_button(i).map(MappedEvent::ButtonRelease),
             EventData::ButtonValue(i, val) =>
-                self.gamepad.mapping.map_button(i).map(|b|
-                    MappedEvent::ButtonValue(b, val)
-                ),
+                self.gamepad.map_button(i).map(|b| MappedEvent::ButtonValue(b, val)),
             _ => None
+        };
+        if let Some(event) = mapped {
+            f(event);
         }
     }
 }
@@ -103,6 +204,9 @@ impl Display for EventData {
         match self {
             &EventData::Connected => write!(f, "connected"),
             &EventData::Disconnected => write!(f, "disconnected"),
+            &EventData::Reconfigured => write!(f, "reconfigured"),
+            &EventData::FocusLost => write!(f, "focus lost"),
+            &EventData::FocusGained => write!(f, "focus gained"),
             &EventData::Axis(index, value) => write!(f, "Axis {}: {:.3}", index, value),
             &EventData::Button(index, pressed) => write!(f, "Button {}: {}", index, if pressed { "pressed" } else { "released" }),
             &EventData::ButtonValue(index, value) => write!(f, "Button {}: {}", index, value),
@@ -128,56 +232,76 @@ mod tests {
         Button,
     };
     use ::gamepad::GamepadMappingType;
+    use ::sdl::{
+        SdlDatabase,
+        SdlMapping,
+    };
 
     #[test]
     fn test_event_mapping() {
-        let mapped_pad = Rc::new(GamepadDescription {
-            index: 0,
-            name: String::from(""),
-            mapping: GamepadMappingType::Standard,
-            axis_count: 4,
-            button_count: 16,
-        });
-
-        let unmapped_pad = Rc::new(GamepadDescription {
-            index: 0,
-            name: String::from(""),
-            mapping: GamepadMappingType::NoMapping,
-            axis_count: 4,
-            button_count: 16,
-        });
-
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::Axis(1, 0.5) }.map(),
+        let mapped_pad = Rc::new(GamepadDescription::new(0, String::from(""), GamepadMappingType::Standard, 4, 16));
+
+        let unmapped_pad = Rc::new(GamepadDescription::new(0, String::from(""), GamepadMappingType::NoMapping, 4, 16));
+
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::Axis(1, 0.5), 0.0, 0).map(),
             Some(MappedEvent::Axis(Axis::LeftStickY, 0.5))
         );
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::Axis(5, -0.5) }.map(),
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::Axis(5, -0.5), 0.0, 0).map(),
             None
         );
-        assert_eq!(Event { gamepad: unmapped_pad.clone(), data: EventData::Axis(1, 0.5) }.map(),
+        assert_eq!(Event::new(unmapped_pad.clone(), EventData::Axis(1, 0.5), 0.0, 0).map(),
             None
         );
 
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::Button(1, true) }.map(),
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::Button(1, true), 0.0, 0).map(),
             Some(MappedEvent::ButtonPress(Button::East))
         );
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::Button(16, false) }.map(),
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::Button(16, false), 0.0, 0).map(),
             Some(MappedEvent::ButtonRelease(Button::Home))
         );
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::Button(17, true) }.map(),
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::Button(17, true), 0.0, 0).map(),
             None
         );
-        assert_eq!(Event { gamepad: unmapped_pad.clone(), data: EventData::Button(1, true) }.map(),
+        assert_eq!(Event::new(unmapped_pad.clone(), EventData::Button(1, true), 0.0, 0).map(),
             None
         );
 
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::ButtonValue(2, 0.5) }.map(),
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::ButtonValue(2, 0.5), 0.0, 0).map(),
             Some(MappedEvent::ButtonValue(Button::West, 0.5))
         );
-        assert_eq!(Event { gamepad: mapped_pad.clone(), data: EventData::ButtonValue(1000, 0.5) }.map(),
+        assert_eq!(Event::new(mapped_pad.clone(), EventData::ButtonValue(1000, 0.5), 0.0, 0).map(),
             None
         );
-        assert_eq!(Event { gamepad: unmapped_pad.clone(), data: EventData::ButtonValue(2, 0.5) }.map(),
+        assert_eq!(Event::new(unmapped_pad.clone(), EventData::ButtonValue(2, 0.5), 0.0, 0).map(),
             None
         );
     }
+
+    #[test]
+    fn test_event_sdl_mapping() {
+        let mapping: SdlMapping = "030000005e0400008e02000010010000,Pad,a:b0,lefttrigger:a2,-rightx:-a3,+rightx:+a4,"
+            .parse()
+            .unwrap();
+        let mut pad = GamepadDescription::new(0, String::from(""), GamepadMappingType::NoMapping, 7, 4);
+        pad.sdl_mapping = Some(Rc::new(mapping));
+        let pad = Rc::new(pad);
+        let map = |pad: &Rc<GamepadDescription>, data| {
+            let mut events = vec![];
+            Event::new(pad.clone(), data, 0.0, 0).map_into(&mut events);
+            events
+        };
+
+        assert_eq!(map(&pad, EventData::Button(0, true)), vec![MappedEvent::ButtonPress(Button::South)]);
+        assert_eq!(map(&pad, EventData::Axis(2, 0.0)), vec![MappedEvent::ButtonValue(Button::LT2, 0.5)]);
+        assert_eq!(map(&pad, EventData::Axis(3, -0.5)), vec![MappedEvent::Axis(Axis::RightStickX, -0.5)]);
+        assert_eq!(map(&pad, EventData::Axis(4, 0.25)), vec![MappedEvent::Axis(Axis::RightStickX, 0.25)]);
+
+        // SDL numbers hats apart from axes, so the left stick doesn't drive the directional pad
+        let mut pad = GamepadDescription::new(0, String::from(""), GamepadMappingType::NoMapping, 8, 11);
+        pad.sdl_mapping = SdlDatabase::bundled().lookup_ids(0x045e, 0x028e).cloned();
+        let pad = Rc::new(pad);
+        assert_eq!(map(&pad, EventData::Axis(0, -1.0)), vec![MappedEvent::Axis(Axis::LeftStickX, -1.0)]);
+        assert_eq!(map(&pad, EventData::Axis(0, 0.3)), vec![MappedEvent::Axis(Axis::LeftStickX, 0.3)]);
+        assert_eq!(map(&pad, EventData::Button(7, true)), vec![MappedEvent::ButtonPress(Button::Start)]);
+    }
 }
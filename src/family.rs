@@ -0,0 +1,158 @@
+use ::identity::DeviceIdentity;
+use ::mapping::Button;
+
+/// A family of controllers, sharing button labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerFamily {
+    /// Microsoft Xbox controllers: A, B, X, Y.
+    Xbox,
+    /// Sony PlayStation controllers: ✕, ○, □, △.
+    PlayStation,
+    /// Nintendo controllers: B, A, Y, X (with A on the east).
+    Nintendo,
+    /// Any other controller, labelled by position.
+    Generic,
+}
+
+const MICROSOFT: u16 = 0x045e;
+const SONY: u16 = 0x054c;
+const NINTENDO: u16 = 0x057e;
+
+impl ControllerFamily {
+
+    /// Detect the family from USB vendor and product IDs.
+    ///
+    /// Only first-party controllers are recognized; others are `Generic`.
+    pub fn from_ids(vendor_id: u16, _product_id: u16) -> Self {
+        match vendor_id {
+            MICROSOFT => ControllerFamily::Xbox,
+            SONY => ControllerFamily::PlayStation,
+            NINTENDO => ControllerFamily::Nintendo,
+            _ => ControllerFamily::Generic,
+        }
+    }
+
+    /// Detect the family of a device, or `Generic` if its identity is unknown.
+    pub fn from_identity(identity: Option<&DeviceIdentity>) -> Self {
+        identity.map_or(ControllerFamily::Generic, |identity|
+            ControllerFamily::from_ids(identity.vendor_id, identity.product_id)
+        )
+    }
+}
+
+impl Button {
+
+    /// The (label, glyph id) pair of this button.
+    fn names(self, family: ControllerFamily) -> (&'static str, &'static str) {
+        use self::ControllerFamily::*;
+
+        match (family, self) {
+            (Xbox, Button::South) => ("A", "xbox_a"),
+            (Xbox, Button::East) => ("B", "xbox_b"),
+            (Xbox, Button::West) => ("X", "xbox_x"),
+            (Xbox, Button::North) => ("Y", "xbox_y"),
+            (Xbox, Button::LT1) => ("LB", "xbox_lb"),
+            (Xbox, Button::RT1) => ("RB", "xbox_rb"),
+            (Xbox, Button::LT2) => ("LT", "xbox_lt"),
+            (Xbox, Button::RT2) => ("RT", "xbox_rt"),
+            (Xbox, Button::Select) => ("View", "xbox_view"),
+            (Xbox, Button::Start) => ("Menu", "xbox_menu"),
+            (Xbox, Button::LeftStick) => ("LS", "xbox_ls"),
+            (Xbox, Button::RightStick) => ("RS", "xbox_rs"),
+            (Xbox, Button::Home) => ("Xbox", "xbox_guide"),
+
+            (PlayStation, Button::South) => ("\u{2715}", "playstation_cross"),
+            (PlayStation, Button::East) => ("\u{25cb}", "playstation_circle"),
+            (PlayStation, Button::West) => ("\u{25a1}", "playstation_square"),
+            (PlayStation, Button::North) => ("\u{25b3}", "playstation_triangle"),
+            (PlayStation, Button::LT1) => ("L1", "playstation_l1"),
+            (PlayStation, Button::RT1) => ("R1", "playstation_r1"),
+            (PlayStation, Button::LT2) => ("L2", "playstation_l2"),
+            (PlayStation, Button::RT2) => ("R2", "playstation_r2"),
+            (PlayStation, Button::Select) => ("Share", "playstation_share"),
+            (PlayStation, Button::Start) => ("Options", "playstation_options"),
+            (PlayStation, Button::LeftStick) => ("L3", "playstation_l3"),
+            (PlayStation, Button::RightStick) => ("R3", "playstation_r3"),
+            (PlayStation, Button::Home) => ("PS", "playstation_ps"),
+
+            (Nintendo, Button::South) => ("B", "nintendo_b"),
+            (Nintendo, Button::East) => ("A", "nintendo_a"),
+            (Nintendo, Button::West) => ("Y", "nintendo_y"),
+            (Nintendo, Button::North) => ("X", "nintendo_x"),
+            (Nintendo, Button::LT1) => ("L", "nintendo_l"),
+            (Nintendo, Button::RT1) => ("R", "nintendo_r"),
+            (Nintendo, Button::LT2) => ("ZL", "nintendo_zl"),
+            (Nintendo, Button::RT2) => ("ZR", "nintendo_zr"),
+            (Nintendo, Button::Select) => ("\u{2212}", "nintendo_minus"),
+            (Nintendo, Button::Start) => ("+", "nintendo_plus"),
+            (Nintendo, Button::LeftStick) => ("L Stick", "nintendo_ls"),
+            (Nintendo, Button::RightStick) => ("R Stick", "nintendo_rs"),
+            (Nintendo, Button::Home) => ("Home", "nintendo_home"),
+
+            (Generic, Button::South) => ("South", "generic_south"),
+            (Generic, Button::East) => ("East", "generic_east"),
+            (Generic, Button::West) => ("West", "generic_west"),
+            (Generic, Button::North) => ("North", "generic_north"),
+            (Generic, Button::LT1) => ("L1", "generic_l1"),
+            (Generic, Button::RT1) => ("R1", "generic_r1"),
+            (Generic, Button::LT2) => ("L2", "generic_l2"),
+            (Generic, Button::RT2) => ("R2", "generic_r2"),
+            (Generic, Button::Select) => ("Select", "generic_select"),
+            (Generic, Button::Start) => ("Start", "generic_start"),
+            (Generic, Button::LeftStick) => ("L3", "generic_l3"),
+            (Generic, Button::RightStick) => ("R3", "generic_r3"),
+            (Generic, Button::Home) => ("Home", "generic_home"),
+
+            // the directional pad looks the same on every family
+            (_, Button::Up) => ("Up", "dpad_up"),
+            (_, Button::Down) => ("Down", "dpad_down"),
+            (_, Button::Left) => ("Left", "dpad_left"),
+            (_, Button::Right) => ("Right", "dpad_right"),
+        }
+    }
+
+    /// The label printed on this button, for controllers of this family.
+    ///
+    /// For example, [South](#variant.South) is "A" on Xbox and "✕" on PlayStation.
+    pub fn label(self, family: ControllerFamily) -> &'static str {
+        self.names(family).0
+    }
+
+    /// A stable key for this button's icon, for controllers of this family.
+    ///
+    /// Keys are lowercase ASCII, such as "xbox_a" or "playstation_cross",
+    /// and will not change between versions.
+    pub fn glyph_id(self, family: ControllerFamily) -> &'static str {
+        self.names(family).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_family_from_ids() {
+        assert_eq!(ControllerFamily::from_ids(0x045e, 0x028e), ControllerFamily::Xbox);
+        assert_eq!(ControllerFamily::from_ids(0x054c, 0x09cc), ControllerFamily::PlayStation);
+        assert_eq!(ControllerFamily::from_ids(0x057e, 0x2009), ControllerFamily::Nintendo);
+        assert_eq!(ControllerFamily::from_ids(0x0079, 0x0011), ControllerFamily::Generic);
+
+        let identity = DeviceIdentity::parse("054c-05c4-Wireless Controller");
+        assert_eq!(ControllerFamily::from_identity(identity.as_ref()), ControllerFamily::PlayStation);
+        assert_eq!(ControllerFamily::from_identity(None), ControllerFamily::Generic);
+    }
+
+    #[test]
+    fn test_button_labels() {
+        assert_eq!(Button::South.label(ControllerFamily::Xbox), "A");
+        assert_eq!(Button::South.label(ControllerFamily::PlayStation), "\u{2715}");
+        assert_eq!(Button::South.label(ControllerFamily::Nintendo), "B");
+        assert_eq!(Button::East.label(ControllerFamily::Nintendo), "A");
+        assert_eq!(Button::LT2.label(ControllerFamily::Nintendo), "ZL");
+        assert_eq!(Button::Up.label(ControllerFamily::PlayStation), "Up");
+
+        assert_eq!(Button::South.glyph_id(ControllerFamily::PlayStation), "playstation_cross");
+        assert_eq!(Button::Start.glyph_id(ControllerFamily::Generic), "generic_start");
+    }
+}
@@ -1,5 +1,30 @@
-use stdweb::web::Gamepad;
-pub use stdweb::web::GamepadMappingType;
+use ::family::ControllerFamily;
+use ::identity::DeviceIdentity;
+use ::mapping::{
+    Axis,
+    Button,
+    Mapping,
+};
+use ::rumble::RumbleCapabilities;
+use ::sdl::{
+    SdlBinding,
+    SdlInput,
+    SdlMapping,
+    SdlTarget,
+};
+use ::table::MappingTable;
+
+use std::rc::Rc;
+
+/// The button/axis layout of a gamepad, as reported by the browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadMappingType {
+    /// The layout is unknown. Button and axis indices depend on the device, browser, and system drivers.
+    NoMapping,
+    /// The [standard gamepad](https://www.w3.org/TR/gamepad/#remapping) layout.
+    Standard,
+}
 
 /// A change in axis or button state.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -14,6 +39,7 @@ pub enum GamepadStateChange {
 
 /// Information about a gamepad. Remains constant while the gamepad is connected.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GamepadDescription {
     /// The index at which this gamepad connected.
     ///
@@ -34,18 +60,113 @@ pub struct GamepadDescription {
 
     /// The number of buttons this gamepad reports.
     pub button_count: usize,
+
+    /// An [SDL mapping](struct.SdlMapping.html) for this gamepad,
+    /// if its browser mapping is unknown.
+    ///
+    /// The [Monitor](struct.Monitor.html) looks this up in its
+    /// [SdlDatabase](struct.SdlDatabase.html) when the gamepad connects.
+    pub sdl_mapping: Option<Rc<SdlMapping>>,
+
+    /// A custom mapping, used in place of the browser's and SDL mappings.
+    ///
+    /// The [Monitor](struct.Monitor.html) gets this from its
+    /// [mapping resolver](struct.Monitor.html#method.set_mapping_resolver) when the gamepad connects.
+    pub mapping_table: Option<Rc<MappingTable>>,
+
+    /// The rumble effects this gamepad supports.
+    pub rumble: RumbleCapabilities,
 }
 
-impl<'a> From<&'a Gamepad> for GamepadDescription {
-    fn from(pad: &'a Gamepad) -> Self {
+impl GamepadDescription {
+
+    /// Describe a gamepad.
+    ///
+    /// This is only needed to implement a [GamepadSource](trait.GamepadSource.html);
+    /// the [Monitor](struct.Monitor.html) provides descriptions of connected gamepads.
+    pub fn new(index: i32, name: String, mapping: GamepadMappingType, axis_count: usize, button_count: usize) -> Self {
         Self {
-            index: pad.index(),
-            name: pad.id().into(),
-            mapping: pad.mapping(),
-            axis_count: pad.axes().len(),
-            button_count: pad.buttons().len(),
+            index,
+            name,
+            mapping,
+            axis_count,
+            button_count,
+            sdl_mapping: None,
+            mapping_table: None,
+            rumble: RumbleCapabilities::default(),
+        }
+    }
+
+    /// The USB vendor and product, parsed from the [name](#structfield.name).
+    ///
+    /// Returns `None` if the name does not contain them,
+    /// like some XInput and MFi controllers.
+    pub fn identity(&self) -> Option<DeviceIdentity> {
+        DeviceIdentity::parse(&self.name)
+    }
+
+    /// The family of this controller, detected from its [identity](#method.identity),
+    /// for choosing [button labels](enum.Button.html#method.label).
+    pub fn family(&self) -> ControllerFamily {
+        ControllerFamily::from_identity(self.identity().as_ref())
+    }
+
+    /// The mapping in use: the custom mapping table, the SDL mapping, or the browser's mapping, in that order.
+    fn active_mapping(&self) -> &dyn Mapping {
+        if let Some(ref table) = self.mapping_table {
+            return table.as_ref();
+        }
+        match self.sdl_mapping {
+            Some(ref sdl) => sdl.as_ref(),
+            None => &self.mapping,
         }
     }
+
+    /// The raw input of a standard button, through the mapping in use.
+    ///
+    /// Unlike [button_index()](trait.Mapping.html#tymethod.button_index), this finds buttons bound to axes and hats.
+    pub(crate) fn button_input(&self, button: Button) -> Option<SdlInput> {
+        match self.bindings() {
+            Some(mut bindings) => bindings
+                .find(|binding| binding.target == SdlTarget::Button(button))
+                .map(|binding| binding.input),
+            None => self.mapping.button_index(button).map(SdlInput::Button),
+        }
+    }
+
+    /// The bindings of the mapping table, or else the SDL mapping, if either is in use.
+    ///
+    /// SDL numbers hats apart from axes, while browsers report them as axes,
+    /// so an SDL mapping's hat bindings are left out.
+    /// A mapping table's hats are axis indices, as bound by [bind_hat()](struct.MappingTable.html#method.bind_hat).
+    pub(crate) fn bindings(&self) -> Option<impl Iterator<Item = &SdlBinding>> {
+        let sdl = self.mapping_table.is_none();
+        let bindings = match self.mapping_table {
+            Some(ref table) => table.bindings(),
+            None => &self.sdl_mapping.as_ref()?.bindings[..],
+        };
+        Some(bindings.iter().filter(move |binding| !(sdl && matches!(binding.input, SdlInput::Hat(..)))))
+    }
+}
+
+/// Maps through the custom mapping table if there is one, then the SDL mapping,
+/// otherwise through the browser's mapping.
+impl Mapping for GamepadDescription {
+    fn map_button(&self, index: usize) -> Option<Button> {
+        self.active_mapping().map_button(index)
+    }
+
+    fn button_index(&self, button: Button) -> Option<usize> {
+        self.active_mapping().button_index(button)
+    }
+
+    fn map_axis(&self, index: usize) -> Option<Axis> {
+        self.active_mapping().map_axis(index)
+    }
+
+    fn axis_index(&self, axis: Axis) -> Option<usize> {
+        self.active_mapping().axis_index(axis)
+    }
 }
 
 /// Snapshot of a gamepad's state at one instant.
@@ -58,6 +179,17 @@ pub struct GamepadState {
 
 impl GamepadState {
 
+    /// Create a snapshot from raw values.
+    ///
+    /// `buttons` holds the (pressed, value) pair of each button.
+    pub fn new(timestamp: f64, axes: Vec<f64>, buttons: Vec<(bool, f64)>) -> Self {
+        Self {
+            timestamp,
+            axes,
+            buttons,
+        }
+    }
+
     /// The [DOMHighResTimeStamp](https://developer.mozilla.org/en-US/docs/Web/API/DOMHighResTimeStamp)
     /// when this gamepad was last updated.
     pub fn timestamp(&self) -> f64 {
@@ -104,46 +236,85 @@ impl GamepadState {
         self.buttons[index].1
     }
 
+    /// Get the value of an axis [-1–1], or `None` if there is no such axis.
+    pub fn get_axis(&self, index: usize) -> Option<f64> {
+        self.axes.get(index).cloned()
+    }
+
+    /// Is the button at this index pressed? `None` if there is no such button.
+    pub fn get_button_pressed(&self, index: usize) -> Option<bool> {
+        self.buttons.get(index).map(|button| button.0)
+    }
+
+    /// How much is the button at this index pressed [0–1]? `None` if there is no such button.
+    pub fn get_button_value(&self, index: usize) -> Option<f64> {
+        self.buttons.get(index).map(|button| button.1)
+    }
+
+    /// Replace the timestamp, axes, and buttons, reusing this snapshot's storage.
+    ///
+    /// This lets a [GamepadSource](trait.GamepadSource.html#method.snapshot_into) update snapshots without allocating.
+    pub fn set_values(&mut self, timestamp: f64, axes: &[f64], buttons: &[(bool, f64)]) {
+        self.timestamp = timestamp;
+        self.axes.clear();
+        self.axes.extend_from_slice(axes);
+        self.buttons.clear();
+        self.buttons.extend_from_slice(buttons);
+    }
+
+    pub(crate) fn set_axis(&mut self, index: usize, value: f64) {
+        self.axes[index] = value;
+    }
+
+    pub(crate) fn set_button_pressed(&mut self, index: usize, pressed: bool) {
+        self.buttons[index].0 = pressed;
+    }
+
+    pub(crate) fn set_button_value(&mut self, index: usize, value: f64) {
+        self.buttons[index].1 = value;
+    }
+
+    pub(crate) fn set_timestamp(&mut self, timestamp: f64) {
+        self.timestamp = timestamp;
+    }
+
+    /// Change the number of axes and buttons. Added axes are at 0.0, and added buttons released.
+    pub(crate) fn resize(&mut self, axis_count: usize, button_count: usize) {
+        self.axes.resize(axis_count, 0.0);
+        self.buttons.resize(button_count, (false, 0.0));
+    }
+
     /// Find the changes in this state, compared to a previous state.
     ///
     /// The timestamps are not considered.
     /// `previous` could be newer than `self`, this still returns changes from `previous` to `self`.
+    ///
+    /// Axis changes come first, then each button's press or release, followed by its value change.
+    ///
+    /// If the states have different numbers of axes or buttons,
+    /// those missing from `previous` are compared to 0.0 (released),
+    /// and those missing from `self` are ignored.
     pub fn changes_since<'a>(&'a self, previous: &'a Self) -> impl Iterator<Item=GamepadStateChange> + 'a {
-        let changed_axes = previous.axes.iter()
-            .zip(self.axes.iter())
-            .enumerate()
-            .filter(|(_, (old, new))| old.to_bits() != new.to_bits())
-            .map(|(i, (_, &new))| GamepadStateChange::Axis(i, new));
-
-        let changed_buttons = previous.buttons.iter()
-            .zip(self.buttons.iter())
+        let changed_axes = self.axes.iter()
             .enumerate()
-            .filter(|(_, (old, new))| old.0 != new.0)
-            .map(|(i, (_, &new))| GamepadStateChange::Button(i, new.0));
+            .map(move |(i, new)| (i, previous.axes.get(i).unwrap_or(&0.0), new))
+            .filter(|(_, old, new)| old.to_bits() != new.to_bits())
+            .map(|(i, _, &new)| GamepadStateChange::Axis(i, new));
 
-        let changed_button_values = previous.buttons.iter()
-            .zip(self.buttons.iter())
+        // each button's press or release comes before its value change
+        let changed_buttons = self.buttons.iter()
             .enumerate()
-            .filter(|(_, (old, new))| old.1.to_bits() != new.1.to_bits())
-            .map(|(i, (_, &new))| GamepadStateChange::ButtonValue(i, new.1));
+            .map(move |(i, new)| (i, previous.buttons.get(i).unwrap_or(&(false, 0.0)), new))
+            .flat_map(|(i, old, new)| {
+                let pressed = Some(GamepadStateChange::Button(i, new.0))
+                    .filter(|_| old.0 != new.0);
+                let value = Some(GamepadStateChange::ButtonValue(i, new.1))
+                    .filter(|_| old.1.to_bits() != new.1.to_bits());
+                pressed.into_iter().chain(value)
+            });
 
         changed_axes
             .chain(changed_buttons)
-            .chain(changed_button_values)
-    }
-}
-
-impl<'a> From<&'a Gamepad> for GamepadState {
-
-    /// Snapshot the current gamepad state.
-    fn from(pad: &'a Gamepad) -> Self {
-        Self {
-            timestamp: pad.timestamp(),
-            axes: pad.axes(),
-            buttons: pad.buttons().iter()
-                .map(|b| (b.pressed(), b.value()))
-                .collect(),
-        }
     }
 }
 
@@ -175,13 +346,7 @@ mod tests {
     #[test]
     fn test_gamepad_state_changes_since() {
 
-        let desc = GamepadDescription {
-            index: 0,
-            name: "".into(),
-            mapping: GamepadMappingType::NoMapping,
-            axis_count: 2,
-            button_count: 2,
-        };
+        let desc = GamepadDescription::new(0, "".into(), GamepadMappingType::NoMapping, 2, 2);
 
         let empty: GamepadState = (&desc).into();
 
@@ -227,4 +392,45 @@ mod tests {
         assert!(p1_changes_since_m.contains(&GamepadStateChange::Axis(0, 0.0)));
         assert!(p1_changes_since_m.contains(&GamepadStateChange::Axis(1, 0.0)));
     }
+
+    #[test]
+    fn test_gamepad_state_set_values() {
+
+        let desc = GamepadDescription::new(0, "".into(), GamepadMappingType::NoMapping, 2, 2);
+
+        let empty: GamepadState = (&desc).into();
+        let mut state = empty.clone();
+        state.set_values(5.0, &[0.5, 0.0], &[(true, 1.0), (true, 0.5)]);
+        assert_eq!(state.timestamp(), 5.0);
+
+        // each button's changes are together
+        assert_eq!(state.changes_since(&empty).collect::<Vec<_>>(), vec![
+            GamepadStateChange::Axis(0, 0.5),
+            GamepadStateChange::Button(0, true),
+            GamepadStateChange::ButtonValue(0, 1.0),
+            GamepadStateChange::Button(1, true),
+            GamepadStateChange::ButtonValue(1, 0.5),
+        ]);
+    }
+
+    #[test]
+    fn test_gamepad_state_changes_since_resized() {
+
+        let old = GamepadState::new(1.0, vec![0.5], vec![(true, 1.0), (true, 1.0)]);
+        let new = GamepadState::new(2.0, vec![0.5, -1.0, 0.0], vec![(false, 0.0)]);
+
+        // added inputs are compared to neutral, removed ones ignored
+        assert_eq!(new.changes_since(&old).collect::<Vec<_>>(), vec![
+            GamepadStateChange::Axis(1, -1.0),
+            GamepadStateChange::Button(0, false),
+            GamepadStateChange::ButtonValue(0, 0.0),
+        ]);
+
+        assert_eq!(new.get_axis(1), Some(-1.0));
+        assert_eq!(new.get_axis(3), None);
+        assert_eq!(old.get_button_pressed(1), Some(true));
+        assert_eq!(new.get_button_pressed(1), None);
+        assert_eq!(old.get_button_value(0), Some(1.0));
+        assert_eq!(new.get_button_value(1), None);
+    }
 }
@@ -0,0 +1,135 @@
+/// The format of a gamepad's [name](struct.GamepadDescription.html#structfield.name),
+/// which depends on the browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BrowserFormat {
+    /// `Xbox 360 Controller (STANDARD GAMEPAD Vendor: 045e Product: 028e)`
+    Chrome,
+    /// `045e-028e-Microsoft X-Box 360 pad`
+    ///
+    /// The [evdev backend](struct.EvdevSource.html) also uses this format.
+    Firefox,
+    /// `45e-28e-Xbox 360 Wired Controller`, like Firefox, but without leading zeros.
+    Safari,
+}
+
+/// The USB vendor and product of a gamepad, parsed from its name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeviceIdentity {
+    /// The USB vendor ID.
+    pub vendor_id: u16,
+    /// The USB product ID.
+    pub product_id: u16,
+    /// The product name, without the IDs and other browser annotations.
+    pub product_name: String,
+    /// The format the name was written in.
+    pub browser_format: BrowserFormat,
+}
+
+impl DeviceIdentity {
+
+    /// Parse a gamepad's name.
+    ///
+    /// Returns `None` if the name does not contain vendor and product IDs.
+    pub fn parse(name: &str) -> Option<Self> {
+        parse_chrome(name).or_else(|| parse_dashed(name))
+    }
+}
+
+/// Read the hexadecimal number after `label`.
+fn hex_after(text: &str, label: &str) -> Option<u16> {
+    let start = text.find(label)? + label.len();
+    let digits: String = text[start..].chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+    u16::from_str_radix(&digits, 16).ok()
+}
+
+/// `Name (Vendor: 045e Product: 028e)`, optionally with `STANDARD GAMEPAD` in the parentheses.
+fn parse_chrome(name: &str) -> Option<DeviceIdentity> {
+    let open = name.rfind(" (")?;
+    let details = &name[open..];
+
+    Some(DeviceIdentity {
+        vendor_id: hex_after(details, "Vendor: ")?,
+        product_id: hex_after(details, "Product: ")?,
+        product_name: name[..open].trim().into(),
+        browser_format: BrowserFormat::Chrome,
+    })
+}
+
+/// `045e-028e-Name` (Firefox), or `45e-28e-Name` (Safari).
+fn parse_dashed(name: &str) -> Option<DeviceIdentity> {
+    let mut parts = name.splitn(3, '-');
+    let vendor = parts.next()?;
+    let product = parts.next()?;
+    let product_name = parts.next()?;
+
+    let is_id = |id: &str| !id.is_empty() && id.len() <= 4 && id.chars().all(|c| c.is_ascii_hexdigit());
+    if !is_id(vendor) || !is_id(product) {
+        return None;
+    }
+
+    let browser_format = if vendor.len() == 4 && product.len() == 4 {
+        BrowserFormat::Firefox
+    }
+    else {
+        BrowserFormat::Safari
+    };
+
+    Some(DeviceIdentity {
+        vendor_id: u16::from_str_radix(vendor, 16).ok()?,
+        product_id: u16::from_str_radix(product, 16).ok()?,
+        product_name: product_name.trim().into(),
+        browser_format,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(vendor_id: u16, product_id: u16, product_name: &str, browser_format: BrowserFormat) -> Option<DeviceIdentity> {
+        Some(DeviceIdentity {
+            vendor_id,
+            product_id,
+            product_name: product_name.into(),
+            browser_format,
+        })
+    }
+
+    #[test]
+    fn test_identity_chrome() {
+        assert_eq!(DeviceIdentity::parse("Xbox 360 Controller (STANDARD GAMEPAD Vendor: 045e Product: 028e)"),
+            identity(0x045e, 0x028e, "Xbox 360 Controller", BrowserFormat::Chrome));
+        assert_eq!(DeviceIdentity::parse("Wireless Controller (STANDARD GAMEPAD Vendor: 054c Product: 09cc)"),
+            identity(0x054c, 0x09cc, "Wireless Controller", BrowserFormat::Chrome));
+        assert_eq!(DeviceIdentity::parse("USB Gamepad  (Vendor: 0079 Product: 0011)"),
+            identity(0x0079, 0x0011, "USB Gamepad", BrowserFormat::Chrome));
+        // parentheses in the product name
+        assert_eq!(DeviceIdentity::parse("Pro Controller (Wired) (Vendor: 057e Product: 2009)"),
+            identity(0x057e, 0x2009, "Pro Controller (Wired)", BrowserFormat::Chrome));
+        // XInput pads on Windows have no IDs
+        assert_eq!(DeviceIdentity::parse("Xbox 360 Controller (XInput STANDARD GAMEPAD)"), None);
+    }
+
+    #[test]
+    fn test_identity_firefox() {
+        assert_eq!(DeviceIdentity::parse("045e-028e-Microsoft X-Box 360 pad"),
+            identity(0x045e, 0x028e, "Microsoft X-Box 360 pad", BrowserFormat::Firefox));
+        assert_eq!(DeviceIdentity::parse("054c-05c4-Sony Computer Entertainment Wireless Controller"),
+            identity(0x054c, 0x05c4, "Sony Computer Entertainment Wireless Controller", BrowserFormat::Firefox));
+        assert_eq!(DeviceIdentity::parse("xinput"), None);
+    }
+
+    #[test]
+    fn test_identity_safari() {
+        assert_eq!(DeviceIdentity::parse("45e-28e-Xbox 360 Wired Controller"),
+            identity(0x045e, 0x028e, "Xbox 360 Wired Controller", BrowserFormat::Safari));
+        assert_eq!(DeviceIdentity::parse("54c-9cc-DUALSHOCK 4 Wireless Controller"),
+            identity(0x054c, 0x09cc, "DUALSHOCK 4 Wireless Controller", BrowserFormat::Safari));
+        // MFi controllers have no IDs
+        assert_eq!(DeviceIdentity::parse("Xbox Wireless Controller"), None);
+        assert_eq!(DeviceIdentity::parse("Left-Right-Pad"), None);
+        assert_eq!(DeviceIdentity::parse("12345-28e-Pad"), None);
+    }
+}
@@ -0,0 +1,319 @@
+use ::event::{
+    Event,
+    EventData,
+    MappedEvent,
+    PadMappedEvent,
+};
+use ::mapping::{
+    Axis,
+    Button,
+    Stick,
+};
+use ::monitor::Monitor;
+use ::source::GamepadSource;
+use ::stick::StickPosition;
+use ::trigger::Trigger;
+
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct ButtonInput {
+    pressed: bool,
+    /// When the current press started.
+    pressed_at: f64,
+    /// Presses and releases seen during the current frame.
+    presses: u32,
+    releases: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct PadInput {
+    buttons: BTreeMap<Button, ButtonInput>,
+    axes: BTreeMap<Axis, f64>,
+}
+
+/// The state of each gamepad's standard buttons and axes, updated once per frame.
+///
+/// Unlike the event stream, this answers queries like "is South held",
+/// or "was Start pressed this frame".
+/// A press and release within one frame is reported by both
+/// [just_pressed()](#method.just_pressed) and [just_released()](#method.just_released).
+///
+/// Gamepads are identified by their [index](struct.GamepadDescription.html#structfield.index).
+///
+/// ```no_run
+/// use gamepad_web::*;
+///
+/// let mut monitor = Monitor::new();
+/// let mut input = InputState::new();
+///
+/// # let now = 0.0;
+/// // each frame
+/// input.update(&mut monitor, now);
+/// if input.just_pressed(0, Button::South) {
+///     // jump
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InputState {
+    pads: HashMap<usize, PadInput>,
+    now: f64,
+}
+
+impl InputState {
+
+    /// Create an input state, with no buttons pressed.
+    pub fn new() -> Self {
+        Self {
+            pads: HashMap::new(),
+            now: 0.0,
+        }
+    }
+
+    /// Start a new frame at time `now`, then handle every event in `monitor`.
+    ///
+    /// `now` is in milliseconds, like a
+    /// [DOMHighResTimeStamp](https://developer.mozilla.org/en-US/docs/Web/API/DOMHighResTimeStamp).
+    /// Events are read as the monitor maps them, so triggers read from axes are pressed
+    /// at its [trigger threshold](struct.Monitor.html#method.set_trigger_threshold).
+    /// This consumes the monitor's events; to see them too,
+    /// call [begin_frame()](#method.begin_frame) and [handle_mapped()](#method.handle_mapped) instead.
+    pub fn update<S: GamepadSource>(&mut self, monitor: &mut Monitor<S>, now: f64) {
+        self.begin_frame(now);
+        while let Some(mapped) = monitor.poll_pad_mapped_with(|event| Self::reset_connected(&mut self.pads, event)) {
+            self.handle_mapped(&mapped);
+        }
+    }
+
+    /// Start a new frame at time `now`, forgetting which buttons were just pressed or released.
+    pub fn begin_frame(&mut self, now: f64) {
+        self.now = now;
+        for pad in self.pads.values_mut() {
+            for button in pad.buttons.values_mut() {
+                button.presses = 0;
+                button.releases = 0;
+            }
+        }
+    }
+
+    /// Forget a gamepad's input as it connects.
+    fn reset_connected(pads: &mut HashMap<usize, PadInput>, event: &Event) {
+        if event.data == EventData::Connected && event.gamepad.index >= 0 {
+            pads.insert(event.gamepad.index as usize, PadInput::default());
+        }
+    }
+
+    /// Update the state from a raw event, mapped through its gamepad's mapping.
+    ///
+    /// Raw events don't press triggers read from axes; [handle_mapped()](#method.handle_mapped) does.
+    pub fn handle(&mut self, event: &Event) {
+        if event.gamepad.index < 0 {
+            return;
+        }
+        if event.data == EventData::Connected {
+            return Self::reset_connected(&mut self.pads, event);
+        }
+
+        let index = event.gamepad.index as usize;
+        event.map_each(|mapped| self.apply(index, mapped));
+    }
+
+    /// Update the state from an event mapped by the [Monitor](struct.Monitor.html#method.poll_pad_mapped).
+    pub fn handle_mapped(&mut self, event: &PadMappedEvent) {
+        self.apply(event.pad, event.event);
+    }
+
+    fn apply(&mut self, index: usize, event: MappedEvent) {
+        let now = self.now;
+        let pad = self.pads.entry(index).or_default();
+        match event {
+            MappedEvent::ButtonPress(button) => {
+                let input = pad.buttons.entry(button).or_default();
+                if !input.pressed {
+                    input.pressed = true;
+                    input.pressed_at = now;
+                    input.presses += 1;
+                }
+            },
+            MappedEvent::ButtonRelease(button) => {
+                let input = pad.buttons.entry(button).or_default();
+                if input.pressed {
+                    input.pressed = false;
+                    input.releases += 1;
+                }
+            },
+            MappedEvent::Axis(axis, value) => {
+                pad.axes.insert(axis, value);
+            },
+            MappedEvent::ButtonValue(button, value) => {
+                if let Some(trigger) = Trigger::from_button(button) {
+                    pad.axes.insert(trigger.axis(), value);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn button(&self, pad: usize, button: Button) -> Option<&ButtonInput> {
+        self.pads.get(&pad).and_then(|pad| pad.buttons.get(&button))
+    }
+
+    /// Is this button held down?
+    pub fn pressed(&self, pad: usize, button: Button) -> bool {
+        self.button(pad, button).map_or(false, |input| input.pressed)
+    }
+
+    /// Was this button pressed during the current frame? It may have been released since.
+    pub fn just_pressed(&self, pad: usize, button: Button) -> bool {
+        self.button(pad, button).map_or(false, |input| input.presses > 0)
+    }
+
+    /// Was this button released during the current frame? It may have been pressed again since.
+    pub fn just_released(&self, pad: usize, button: Button) -> bool {
+        self.button(pad, button).map_or(false, |input| input.releases > 0)
+    }
+
+    /// How long this button has been held, in milliseconds, or `None` if it is not held.
+    ///
+    /// Presses are timed from the start of the frame in which they were seen.
+    pub fn held_duration(&self, pad: usize, button: Button) -> Option<f64> {
+        self.button(pad, button)
+            .filter(|input| input.pressed)
+            .map(|input| self.now - input.pressed_at)
+    }
+
+    /// The value of this axis [-1–1], or of a trigger [0–1], or 0.0 if unknown.
+    pub fn axis(&self, pad: usize, axis: Axis) -> f64 {
+        self.pads.get(&pad)
+            .and_then(|pad| pad.axes.get(&axis))
+            .cloned()
+            .unwrap_or(0.0)
+    }
+
+    /// The position of this stick.
+    pub fn stick(&self, pad: usize, stick: Stick) -> StickPosition {
+        StickPosition::new(self.axis(pad, stick.x_axis()), self.axis(pad, stick.y_axis()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::{
+        GamepadDescription,
+        GamepadMappingType,
+    };
+    use ::mock::MockSource;
+    use ::sdl::{
+        SdlInput,
+        SdlTarget,
+    };
+    use ::table::MappingTable;
+
+    fn mock_monitor() -> Monitor<MockSource> {
+        let mut monitor = Monitor::with_source(MockSource::new());
+        monitor.source_mut().connect(GamepadDescription::new(0, "pad".into(), GamepadMappingType::Standard, 4, 17));
+        monitor
+    }
+
+    #[test]
+    fn test_input_state_buttons() {
+        let mut monitor = mock_monitor();
+        let mut input = InputState::new();
+
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().bump_timestamp(0);
+        input.update(&mut monitor, 100.0);
+        assert!(input.pressed(0, Button::South));
+        assert!(input.just_pressed(0, Button::South));
+        assert!(!input.just_released(0, Button::South));
+        assert_eq!(input.held_duration(0, Button::South), Some(0.0));
+
+        input.update(&mut monitor, 116.0);
+        assert!(input.pressed(0, Button::South));
+        assert!(!input.just_pressed(0, Button::South));
+        assert_eq!(input.held_duration(0, Button::South), Some(16.0));
+
+        monitor.source_mut().release(0, 0);
+        monitor.source_mut().bump_timestamp(0);
+        input.update(&mut monitor, 132.0);
+        assert!(!input.pressed(0, Button::South));
+        assert!(input.just_released(0, Button::South));
+        assert_eq!(input.held_duration(0, Button::South), None);
+
+        // unknown pads and buttons are released
+        assert!(!input.pressed(0, Button::North));
+        assert!(!input.pressed(1, Button::South));
+    }
+
+    #[test]
+    fn test_input_state_press_release_in_one_frame() {
+        let mut monitor = mock_monitor();
+        let mut input = InputState::new();
+        input.update(&mut monitor, 0.0);
+
+        monitor.source_mut().press(0, 9);
+        monitor.source_mut().bump_timestamp(0);
+        input.begin_frame(16.0);
+        while let Some(event) = monitor.poll() {
+            input.handle(&event);
+        }
+        monitor.source_mut().release(0, 9);
+        monitor.source_mut().bump_timestamp(0);
+        while let Some(event) = monitor.poll() {
+            input.handle(&event);
+        }
+
+        assert!(!input.pressed(0, Button::Start));
+        assert!(input.just_pressed(0, Button::Start));
+        assert!(input.just_released(0, Button::Start));
+
+        input.begin_frame(32.0);
+        assert!(!input.just_pressed(0, Button::Start));
+        assert!(!input.just_released(0, Button::Start));
+    }
+
+    #[test]
+    fn test_input_state_trigger_axis() {
+        let mut table = MappingTable::new();
+        table.bind(SdlTarget::Button(Button::RT2), SdlInput::Axis { index: 2, half: None, inverted: false });
+        let mut monitor = Monitor::with_source(MockSource::new());
+        monitor.set_mapping_resolver(move |_| Some(table.clone()));
+        monitor.source_mut().connect(GamepadDescription::new(0, "pad".into(), GamepadMappingType::NoMapping, 4, 4));
+        let mut input = InputState::new();
+
+        // pressed and released by the monitor, at its trigger threshold
+        monitor.source_mut().set_axis(0, 2, 0.2);
+        monitor.source_mut().bump_timestamp(0);
+        input.update(&mut monitor, 0.0);
+        assert!(input.pressed(0, Button::RT2));
+        assert!(input.just_pressed(0, Button::RT2));
+        assert_eq!(input.axis(0, Axis::RightTrigger), 0.6);
+
+        monitor.source_mut().set_axis(0, 2, -0.5);
+        monitor.source_mut().bump_timestamp(0);
+        input.update(&mut monitor, 16.0);
+        assert!(!input.pressed(0, Button::RT2));
+        assert!(input.just_released(0, Button::RT2));
+        assert_eq!(input.axis(0, Axis::RightTrigger), 0.25);
+    }
+
+    #[test]
+    fn test_input_state_axes() {
+        let mut monitor = mock_monitor();
+        let mut input = InputState::new();
+
+        monitor.source_mut().set_axis(0, 2, 0.5);
+        monitor.source_mut().set_axis(0, 3, -1.0);
+        monitor.source_mut().bump_timestamp(0);
+        input.update(&mut monitor, 0.0);
+
+        assert_eq!(input.axis(0, Axis::RightStickX), 0.5);
+        assert_eq!(input.axis(0, Axis::LeftStickX), 0.0);
+        assert_eq!(input.stick(0, Stick::Right), StickPosition::new(0.5, -1.0));
+        assert_eq!(input.stick(1, Stick::Left), StickPosition::new(0.0, 0.0));
+    }
+}
@@ -45,30 +45,220 @@
 //! # fn set_velocity(x: f64) {}
 //! # fn request_animation_frame(callback: impl FnOnce(f64) + 'static) {}
 //! ```
+//!
+//! ## Backends
+//!
+//! Gamepads are read through [stdweb](https://github.com/koute/stdweb) by default.
+//! For wasm-bindgen projects, disable the default features and enable `web_sys`:
+//!
+//! ```toml
+//! [dependencies]
+//! gamepad-web = { version = "0.1", default-features = false, features = ["web_sys"] }
+//! ```
+//!
+//! Native Linux builds can read `/dev/input` devices with the `evdev` feature,
+//! through [EvdevSource](struct.EvdevSource.html).
+//!
+//! ## Non-standard gamepads
+//!
+//! Browsers only map some gamepads to the standard layout.
+//! For other gamepads, the [Monitor](struct.Monitor.html) looks up a mapping in
+//! [SDL's format](https://github.com/gabomdq/SDL_GameControllerDB),
+//! from a small bundled [SdlDatabase](struct.SdlDatabase.html),
+//! which can be extended through [Monitor::sdl_database_mut()](struct.Monitor.html#method.sdl_database_mut).
+//! For gamepads with no mapping, a [MappingWizard](struct.MappingWizard.html) asks the player
+//! to press each button in turn, and builds one.
+//! To replace any gamepad's mapping, give it a [MappingTable](struct.MappingTable.html) through
+//! [Monitor::set_mapping_resolver()](struct.Monitor.html#method.set_mapping_resolver).
+//!
+//! ## Triggers
+//!
+//! Analog triggers are read as the [LeftTrigger](enum.Axis.html#variant.LeftTrigger) and
+//! [RightTrigger](enum.Axis.html#variant.RightTrigger) axes [0–1], whether the gamepad reports them
+//! as buttons or as axes. Triggers read from axes press their buttons past a
+//! [threshold](struct.Monitor.html#method.set_trigger_threshold).
+//!
+//! ## Actions
+//!
+//! An [ActionMap](struct.ActionMap.html) binds buttons and axes to your own actions,
+//! such as "Jump" or "MoveX", and can be rebound at runtime.
+//!
+//! ## Profiles
+//!
+//! With the `serde` feature, events, mappings, and bindings implement `Serialize` and `Deserialize`.
+//! The `profiles` feature adds [Profile](struct.Profile.html), a player's bindings and mapping
+//! for one kind of gamepad, saved as JSON in [localStorage](struct.LocalStorage.html)
+//! or any other [ProfileStorage](trait.ProfileStorage.html).
+//!
+//! ## Rumble
+//!
+//! Gamepads with vibration actuators report their
+//! [rumble capabilities](struct.GamepadDescription.html#structfield.rumble),
+//! and play effects through [Monitor::rumble()](struct.Monitor.html#method.rumble).
+//! A [RumbleSequencer](struct.RumbleSequencer.html) plays timed sequences of effects.
+//!
+//! ## Focus
+//!
+//! Browsers stop updating gamepads while the page is hidden.
+//! When the page loses focus, the monitor releases every held button and axis,
+//! and emits [FocusLost](enum.EventData.html#variant.FocusLost).
+//! When it regains focus, input held at that point is taken silently,
+//! and [FocusGained](enum.EventData.html#variant.FocusGained) is emitted.
+//!
+//! ## Testing
+//!
+//! The `testing` feature adds [MockSource](struct.MockSource.html),
+//! a scriptable replacement for the browser's gamepads.
+//! Use it with [Monitor::with_source()](struct.Monitor.html#method.with_source)
+//! to test input handling natively, with `cargo test`.
+//!
+//! To reproduce a session, record any source with a [Recorder](struct.Recorder.html),
+//! and play it back with a [ReplaySource](struct.ReplaySource.html).
 
 #![deny(missing_docs)]
 
+#[cfg(feature = "web_sys")]
+extern crate js_sys;
+#[cfg(all(feature = "evdev", target_os = "linux"))]
+extern crate libc;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "profiles")]
+extern crate serde_json;
+#[cfg(feature = "stdweb")]
+#[macro_use]
 extern crate stdweb;
+#[cfg(feature = "web_sys")]
+extern crate wasm_bindgen;
+#[cfg(feature = "web_sys")]
+extern crate web_sys;
 
+mod action;
+mod deadzone;
 mod event;
+#[cfg(all(feature = "evdev", target_os = "linux"))]
+mod evdev;
+mod family;
 mod gamepad;
+mod identity;
+mod input;
 mod mapping;
+#[cfg(any(test, feature = "testing"))]
+mod mock;
 mod monitor;
+mod players;
+#[cfg(feature = "profiles")]
+mod profile;
+mod recording;
+mod rumble;
+mod sdl;
+mod source;
+mod stick;
+mod table;
+mod trigger;
+#[cfg(feature = "stdweb")]
+mod stdweb_source;
+#[cfg(feature = "web_sys")]
+mod web_sys_source;
+mod wizard;
 
+pub use action::{
+    ActionEvent,
+    ActionMap,
+    Binding,
+    BindingConflict,
+};
+pub use deadzone::{
+    DeadZone,
+    DeadZoneShape,
+    DeadZones,
+};
+#[cfg(all(feature = "evdev", target_os = "linux"))]
+pub use evdev::{
+    AbsInfo,
+    EvdevDevice,
+    EvdevInfo,
+    EvdevSource,
+};
 pub use event::{
     Event,
     EventData,
     MappedEvent,
+    PadMappedEvent,
+    PlayerMappedEvent,
 };
+pub use family::ControllerFamily;
 pub use gamepad::{
     GamepadDescription,
     GamepadMappingType,
     GamepadState,
     GamepadStateChange,
 };
+pub use identity::{
+    BrowserFormat,
+    DeviceIdentity,
+};
+pub use input::InputState;
 pub use mapping::{
     Axis,
     Button,
     Mapping,
+    Stick,
 };
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockSource;
 pub use monitor::Monitor;
+pub use players::{
+    PlayerEvent,
+    PlayerSlots,
+};
+#[cfg(feature = "profiles")]
+pub use profile::{
+    MemoryStorage,
+    Profile,
+    ProfileError,
+    ProfileKey,
+    ProfileStorage,
+};
+#[cfg(all(feature = "profiles", any(feature = "stdweb", feature = "web_sys")))]
+pub use profile::LocalStorage;
+pub use recording::{
+    Recorder,
+    Recording,
+    RecordingError,
+    ReplaySource,
+};
+pub use rumble::{
+    RumbleCapabilities,
+    RumbleEffect,
+    RumbleError,
+    RumblePattern,
+    RumbleSequencer,
+};
+pub use sdl::{
+    AxisHalf,
+    SdlBinding,
+    SdlDatabase,
+    SdlInput,
+    SdlMapping,
+    SdlParseError,
+    SdlTarget,
+};
+pub use source::{
+    ConnectionChange,
+    FocusState,
+    GamepadSource,
+};
+#[cfg(feature = "stdweb")]
+pub use stdweb_source::StdwebSource;
+pub use stick::{
+    Direction4,
+    Direction8,
+    StickPosition,
+};
+pub use table::MappingTable;
+pub use trigger::Trigger;
+#[cfg(feature = "web_sys")]
+pub use web_sys_source::WebSysSource;
+pub use wizard::MappingWizard;
@@ -1,7 +1,10 @@
 use ::gamepad::GamepadMappingType;
 
 /// A named button on the standard gamepad.
+///
+/// Use [label()](#method.label) to show its name on a particular controller.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Button {
     /// "A" on Xbox, "cross" on PlayStation, "B" on Nintendo.
     South,
@@ -41,6 +44,7 @@ pub enum Button {
 
 /// A named axis on the standard gamepad.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Axis {
     /// From left (-1.0) to right (1.0).
     LeftStickX,
@@ -50,6 +54,39 @@ pub enum Axis {
     RightStickX,
     /// From up (-1.0) to down (1.0).
     RightStickY,
+    /// From released (0.0) to fully pressed (1.0). See [Trigger](enum.Trigger.html).
+    LeftTrigger,
+    /// From released (0.0) to fully pressed (1.0). See [Trigger](enum.Trigger.html).
+    RightTrigger,
+}
+
+/// An analog stick on the standard gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Stick {
+    /// The left stick: [LeftStickX](enum.Axis.html#variant.LeftStickX) and [LeftStickY](enum.Axis.html#variant.LeftStickY).
+    Left,
+    /// The right stick: [RightStickX](enum.Axis.html#variant.RightStickX) and [RightStickY](enum.Axis.html#variant.RightStickY).
+    Right,
+}
+
+impl Stick {
+
+    /// The horizontal axis of this stick.
+    pub fn x_axis(self) -> Axis {
+        match self {
+            Stick::Left => Axis::LeftStickX,
+            Stick::Right => Axis::RightStickX,
+        }
+    }
+
+    /// The vertical axis of this stick.
+    pub fn y_axis(self) -> Axis {
+        match self {
+            Stick::Left => Axis::LeftStickY,
+            Stick::Right => Axis::RightStickY,
+        }
+    }
 }
 
 /// A relation between indices and names, for buttons and axes.
@@ -64,87 +101,60 @@ pub trait Mapping {
     fn axis_index(&self, axis: Axis) -> Option<usize>;
 }
 
+/// The buttons of the [standard gamepad](https://www.w3.org/TR/gamepad/#remapping), by index.
+pub(crate) const STANDARD_BUTTONS: [Button; 17] = [
+    Button::South,
+    Button::East,
+    Button::West,
+    Button::North,
+    Button::LT1,
+    Button::RT1,
+    Button::LT2,
+    Button::RT2,
+    Button::Select,
+    Button::Start,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::Up,
+    Button::Down,
+    Button::Left,
+    Button::Right,
+    Button::Home,
+];
+
+/// The axes of the standard gamepad, by index.
+pub(crate) const STANDARD_AXES: [Axis; 4] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::RightStickX,
+    Axis::RightStickY,
+];
+
 impl Mapping for GamepadMappingType {
     fn map_button(&self, index: usize) -> Option<Button> {
         match self {
-            GamepadMappingType::Standard =>
-                match index {
-                    0 => Some(Button::South),
-                    1 => Some(Button::East),
-                    2 => Some(Button::West),
-                    3 => Some(Button::North),
-                    4 => Some(Button::LT1),
-                    5 => Some(Button::RT1),
-                    6 => Some(Button::LT2),
-                    7 => Some(Button::RT2),
-                    8 => Some(Button::Select),
-                    9 => Some(Button::Start),
-                    10 => Some(Button::LeftStick),
-                    11 => Some(Button::RightStick),
-                    12 => Some(Button::Up),
-                    13 => Some(Button::Down),
-                    14 => Some(Button::Left),
-                    15 => Some(Button::Right),
-                    16 => Some(Button::Home),
-                    _ => None,
-                },
+            GamepadMappingType::Standard => STANDARD_BUTTONS.get(index).cloned(),
             _ => None,
         }
     }
 
     fn button_index(&self, button: Button) -> Option<usize> {
-        #![allow(unreachable_patterns)]
         match self {
-            GamepadMappingType::Standard =>
-                match button {
-                    Button::South => Some(0),
-                    Button::East => Some(1),
-                    Button::West => Some(2),
-                    Button::North => Some(3),
-                    Button::LT1 => Some(4),
-                    Button::RT1 => Some(5),
-                    Button::LT2 => Some(6),
-                    Button::RT2 => Some(7),
-                    Button::Select => Some(8),
-                    Button::Start => Some(9),
-                    Button::LeftStick => Some(10),
-                    Button::RightStick => Some(11),
-                    Button::Up => Some(12),
-                    Button::Down => Some(13),
-                    Button::Left => Some(14),
-                    Button::Right => Some(15),
-                    Button::Home => Some(16),
-                    _ => None,
-                },
+            GamepadMappingType::Standard => STANDARD_BUTTONS.iter().position(|&b| b == button),
             _ => None,
         }
     }
 
     fn map_axis(&self, index: usize) -> Option<Axis> {
         match self {
-            GamepadMappingType::Standard =>
-                match index {
-                    0 => Some(Axis::LeftStickX),
-                    1 => Some(Axis::LeftStickY),
-                    2 => Some(Axis::RightStickX),
-                    3 => Some(Axis::RightStickY),
-                    _ => None,
-                },
+            GamepadMappingType::Standard => STANDARD_AXES.get(index).cloned(),
             _ => None,
         }
     }
 
     fn axis_index(&self, axis: Axis) -> Option<usize> {
-        #![allow(unreachable_patterns)]
         match self {
-            GamepadMappingType::Standard =>
-                match axis {
-                    Axis::LeftStickX => Some(0),
-                    Axis::LeftStickY => Some(1),
-                    Axis::RightStickX => Some(2),
-                    Axis::RightStickY => Some(3),
-                    _ => None,
-                },
+            GamepadMappingType::Standard => STANDARD_AXES.iter().position(|&a| a == axis),
             _ => None,
         }
     }
@@ -0,0 +1,302 @@
+use ::gamepad::{
+    GamepadDescription,
+    GamepadMappingType,
+    GamepadState,
+};
+use ::rumble::{
+    RumbleEffect,
+    RumbleError,
+};
+use ::source::{
+    ConnectionChange,
+    FocusState,
+    FocusTracker,
+    GamepadSource,
+};
+
+use std::mem;
+
+#[derive(Debug, Clone)]
+struct MockPad {
+    desc: GamepadDescription,
+    timestamp: f64,
+    axes: Vec<f64>,
+    buttons: Vec<(bool, f64)>,
+    rumble: Vec<Option<RumbleEffect>>,
+}
+
+/// A scriptable gamepad source, for testing without a browser.
+///
+/// Requires the `testing` feature.
+///
+/// Changes are only visible to a [Monitor](struct.Monitor.html) once the gamepad's timestamp changes,
+/// just like the browser's gamepads.
+/// Call [bump_timestamp()](#method.bump_timestamp) after setting inputs.
+///
+/// ```
+/// # extern crate gamepad_web;
+/// use gamepad_web::*;
+///
+/// # fn main() {
+/// let mut monitor = Monitor::with_source(MockSource::new());
+///
+/// monitor.source_mut().connect(GamepadDescription::new(0, "pad".into(), GamepadMappingType::Standard, 4, 17));
+/// monitor.source_mut().press(0, 0);
+/// monitor.source_mut().bump_timestamp(0);
+///
+/// assert_eq!(monitor.poll().unwrap().data, EventData::Connected);
+/// assert_eq!(monitor.poll().unwrap().data, EventData::Button(0, true));
+/// assert_eq!(monitor.poll().unwrap().data, EventData::ButtonValue(0, 1.0));
+/// assert_eq!(monitor.poll(), None);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockSource {
+    pads: Vec<Option<MockPad>>,
+    focus: FocusTracker,
+    connection_events: bool,
+    connection_changes: Vec<ConnectionChange>,
+}
+
+impl MockSource {
+
+    /// Create a source with no gamepad slots.
+    pub fn new() -> Self {
+        Self {
+            pads: vec![],
+            focus: FocusTracker::new(),
+            connection_events: false,
+            connection_changes: vec![],
+        }
+    }
+
+    /// The number of gamepad slots, including empty slots.
+    pub fn len(&self) -> usize {
+        self.pads.len()
+    }
+
+    /// Are there no gamepad slots?
+    pub fn is_empty(&self) -> bool {
+        self.pads.is_empty()
+    }
+
+    /// Grow (adding empty slots) or shrink (dropping any gamepads) to `len` slots.
+    pub fn resize(&mut self, len: usize) {
+        self.pads.resize(len, None);
+    }
+
+    /// Choose whether [connect()](#method.connect) and [disconnect()](#method.disconnect)
+    /// are also reported as [connection changes](trait.GamepadSource.html#method.connection_changes),
+    /// like the browser's `gamepadconnected` and `gamepaddisconnected` events.
+    ///
+    /// Disabled by default, so gamepads are only seen by snapshots.
+    pub fn set_connection_events(&mut self, enabled: bool) {
+        self.connection_events = enabled;
+    }
+
+    /// Plug a gamepad into slot `desc.index`, growing the slot list if needed.
+    ///
+    /// Any gamepad already in that slot is replaced.
+    /// The new gamepad has all axes at 0.0, all buttons released, and timestamp 0.0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `desc.index` is negative.
+    pub fn connect(&mut self, desc: GamepadDescription) {
+        assert!(desc.index >= 0, "negative gamepad index {}", desc.index);
+        let index = desc.index as usize;
+        if index >= self.pads.len() {
+            self.resize(index + 1);
+        }
+
+        self.pads[index] = Some(MockPad {
+            timestamp: 0.0,
+            axes: vec![0.0; desc.axis_count],
+            buttons: vec![(false, 0.0); desc.button_count],
+            rumble: vec![],
+            desc: desc.clone(),
+        });
+        if self.connection_events {
+            let state = (&desc).into();
+            self.connection_changes.push(ConnectionChange::Connected(desc, state));
+        }
+    }
+
+    /// Unplug the gamepad in this slot, leaving the slot empty.
+    ///
+    /// Does nothing if the slot is already empty.
+    pub fn disconnect(&mut self, index: usize) {
+        if let Some(pad) = self.pads.get_mut(index) {
+            if pad.take().is_some() && self.connection_events {
+                self.connection_changes.push(ConnectionChange::Disconnected(index));
+            }
+        }
+    }
+
+    /// Is a gamepad plugged into this slot?
+    pub fn is_connected(&self, index: usize) -> bool {
+        matches!(self.pads.get(index), Some(&Some(_)))
+    }
+
+    fn pad(&self, index: usize) -> &MockPad {
+        self.pads.get(index)
+            .and_then(|pad| pad.as_ref())
+            .unwrap_or_else(|| panic!("no mock gamepad connected at index {}", index))
+    }
+
+    fn pad_mut(&mut self, index: usize) -> &mut MockPad {
+        self.pads.get_mut(index)
+            .and_then(|pad| pad.as_mut())
+            .unwrap_or_else(|| panic!("no mock gamepad connected at index {}", index))
+    }
+
+    /// Set the value of an axis [-1–1].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`, or it has no such axis.
+    pub fn set_axis(&mut self, index: usize, axis: usize, value: f64) {
+        self.pad_mut(index).axes[axis] = value;
+    }
+
+    /// Set the state of a button: whether it is pressed, and how much [0–1].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`, or it has no such button.
+    pub fn set_button(&mut self, index: usize, button: usize, pressed: bool, value: f64) {
+        self.pad_mut(index).buttons[button] = (pressed, value);
+    }
+
+    /// Fully press a button.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`, or it has no such button.
+    pub fn press(&mut self, index: usize, button: usize) {
+        self.set_button(index, button, true, 1.0);
+    }
+
+    /// Fully release a button.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`, or it has no such button.
+    pub fn release(&mut self, index: usize, button: usize) {
+        self.set_button(index, button, false, 0.0);
+    }
+
+    /// Change a gamepad's layout without disconnecting it, as when its driver reinitializes.
+    ///
+    /// Added axes are at 0.0 and added buttons released; other inputs keep their state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`.
+    pub fn reconfigure(&mut self, index: usize, mapping: GamepadMappingType, axis_count: usize, button_count: usize) {
+        let pad = self.pad_mut(index);
+        pad.desc.mapping = mapping;
+        pad.desc.axis_count = axis_count;
+        pad.desc.button_count = button_count;
+        pad.axes.resize(axis_count, 0.0);
+        pad.buttons.resize(button_count, (false, 0.0));
+    }
+
+    /// Set the timestamp of a gamepad.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`.
+    pub fn set_timestamp(&mut self, index: usize, timestamp: f64) {
+        self.pad_mut(index).timestamp = timestamp;
+    }
+
+    /// Advance the timestamp of a gamepad by 1.0, so the monitor sees its new state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`.
+    pub fn bump_timestamp(&mut self, index: usize) {
+        self.pad_mut(index).timestamp += 1.0;
+    }
+
+    /// The rumble effects played on a gamepad since it connected, with `None` for each stop.
+    ///
+    /// A gamepad only rumbles if its description reports [rumble capabilities](struct.GamepadDescription.html#structfield.rumble).
+    ///
+    /// # Panics
+    ///
+    /// Panics if no gamepad is connected at `index`.
+    pub fn rumble_history(&self, index: usize) -> &[Option<RumbleEffect>] {
+        &self.pad(index).rumble
+    }
+
+    /// Focus or unfocus the simulated page.
+    ///
+    /// A loss of focus is reported even if focus returns before the monitor checks.
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focus.set_focused(focused);
+    }
+}
+
+impl Default for MockSource {
+    fn default() -> Self {
+        MockSource::new()
+    }
+}
+
+impl GamepadSource for MockSource {
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>> {
+        self.pads.iter()
+            .map(|pad| pad.as_ref().map(|pad| (
+                pad.desc.clone(),
+                GamepadState::new(pad.timestamp, pad.axes.clone(), pad.buttons.clone()),
+            )))
+            .collect()
+    }
+
+    fn snapshot_into(&mut self, pads: &mut Vec<Option<(GamepadDescription, GamepadState)>>) {
+        pads.truncate(self.pads.len());
+        while pads.len() < self.pads.len() {
+            pads.push(None);
+        }
+
+        for (raw, pad) in pads.iter_mut().zip(&self.pads) {
+            match (raw, pad.as_ref()) {
+                (raw, None) => *raw = None,
+                (&mut Some((ref mut desc, ref mut state)), Some(pad)) => {
+                    if *desc != pad.desc {
+                        *desc = pad.desc.clone();
+                    }
+                    state.set_values(pad.timestamp, &pad.axes, &pad.buttons);
+                },
+                (raw, Some(pad)) =>
+                    *raw = Some((pad.desc.clone(), GamepadState::new(pad.timestamp, pad.axes.clone(), pad.buttons.clone()))),
+            }
+        }
+    }
+
+    fn focus(&mut self) -> FocusState {
+        self.focus.check()
+    }
+
+    fn connection_changes(&mut self) -> Vec<ConnectionChange> {
+        mem::take(&mut self.connection_changes)
+    }
+
+    fn rumble(&mut self, index: usize, effect: &RumbleEffect) -> Result<(), RumbleError> {
+        let pad = self.pads.get_mut(index).and_then(Option::as_mut).ok_or(RumbleError::NotConnected)?;
+        pad.desc.rumble.method(effect).ok_or(RumbleError::Unsupported)?;
+        pad.rumble.push(Some(*effect));
+        Ok(())
+    }
+
+    fn stop_rumble(&mut self, index: usize) -> Result<(), RumbleError> {
+        let pad = self.pads.get_mut(index).and_then(Option::as_mut).ok_or(RumbleError::NotConnected)?;
+        if !pad.desc.rumble.any() {
+            return Err(RumbleError::Unsupported);
+        }
+        pad.rumble.push(None);
+        Ok(())
+    }
+}
@@ -1,25 +1,58 @@
+use ::deadzone::DeadZones;
 use ::event::{
     Event,
     EventData,
     MappedEvent,
+    PadMappedEvent,
+    PlayerMappedEvent,
 };
 use ::gamepad::{
     GamepadDescription,
+    GamepadMappingType,
     GamepadState,
+    GamepadStateChange,
 };
+use ::mapping::{
+    Mapping,
+    Stick,
+};
+use ::rumble::{
+    RumbleEffect,
+    RumbleError,
+};
+use ::sdl::{
+    SdlDatabase,
+    SdlInput,
+};
+use ::source::{
+    ConnectionChange,
+    FocusState,
+    GamepadSource,
+};
+#[cfg(feature = "stdweb")]
+use ::stdweb_source::StdwebSource;
+use ::table::MappingTable;
+use ::trigger::{
+    Trigger,
+    TriggerState,
+    TriggerThreshold,
+};
+#[cfg(all(feature = "web_sys", not(feature = "stdweb")))]
+use ::web_sys_source::WebSysSource;
 
-use std::collections::VecDeque;
-use std::iter;
-use std::rc::Rc;
-
-use stdweb::web::{
-    Gamepad,
+use std::collections::{
+    HashMap,
+    VecDeque,
 };
+use std::mem;
+use std::rc::Rc;
 
 #[derive(Debug, PartialEq, Clone)]
 struct ConnectedPad {
     desc: Rc<GamepadDescription>,
     state: GamepadState,
+    /// The left and right triggers.
+    triggers: [TriggerState; 2],
 }
 
 impl From<GamepadDescription> for ConnectedPad {
@@ -28,65 +61,408 @@ impl From<GamepadDescription> for ConnectedPad {
         Self {
             desc: desc.clone(),
             state: desc.as_ref().into(),
+            triggers: [TriggerState::default(); 2],
+        }
+    }
+}
+
+const TRIGGERS: [Trigger; 2] = [Trigger::Left, Trigger::Right];
+
+/// The most events kept queued for other gamepads by [Monitor::poll_mapped_for()](struct.Monitor.html#method.poll_mapped_for).
+const MAX_QUEUED: usize = 1024;
+
+/// A queued event.
+///
+/// Raw events are returned by poll(), and mapped by poll_mapped().
+/// Mapped events combine several raw events, so they are only returned by poll_mapped().
+#[derive(Debug, Clone)]
+enum Queued {
+    /// (event, its axis' previous value)
+    Raw(Event, Option<f64>),
+    Mapped(PadMappedEvent),
+}
+
+impl Queued {
+    /// The index of the gamepad which emitted this event.
+    fn pad(&self) -> usize {
+        match *self {
+            Queued::Raw(ref event, _) => event.gamepad.index as usize,
+            Queued::Mapped(ref mapped) => mapped.pad,
         }
     }
 }
 
+/// Queued events, numbered in order.
+#[derive(Debug, Default)]
+struct EventQueue {
+    queue: VecDeque<Queued>,
+    next_sequence: u64,
+}
+
+impl EventQueue {
+    fn next_sequence(&mut self) -> u64 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        sequence
+    }
+
+    fn push_raw(&mut self, gamepad: Rc<GamepadDescription>, data: EventData, timestamp: f64) {
+        self.push_input(gamepad, data, None, timestamp);
+    }
+
+    /// Queue a raw input event, with its axis' previous value, so only changed inputs are mapped.
+    fn push_input(&mut self, gamepad: Rc<GamepadDescription>, data: EventData, previous: Option<f64>, timestamp: f64) {
+        let sequence = self.next_sequence();
+        self.queue.push_back(Queued::Raw(Event::new(gamepad, data, timestamp, sequence), previous));
+    }
+
+    fn push_mapped(&mut self, pad: usize, event: MappedEvent, timestamp: f64) {
+        let sequence = self.next_sequence();
+        self.queue.push_back(Queued::Mapped(PadMappedEvent { pad, event, timestamp, sequence }));
+    }
+}
+
+/// Chooses a custom mapping for a gamepad, as it connects.
+type MappingResolver = Box<dyn FnMut(&GamepadDescription) -> Option<MappingTable>>;
+
+/// The browser's gamepads, read through the enabled backend.
+#[cfg(feature = "stdweb")]
+type BrowserSource = StdwebSource;
+#[cfg(all(feature = "web_sys", not(feature = "stdweb")))]
+type BrowserSource = WebSysSource;
+
 /// Listen for gamepad events
-pub struct Monitor {
-    queue: VecDeque<Event>,
+///
+/// Gamepads are read from a [GamepadSource](trait.GamepadSource.html),
+/// by default the browser's, through [StdwebSource](struct.StdwebSource.html)
+/// (or [WebSysSource](struct.WebSysSource.html) if only the `web_sys` feature is enabled).
+pub struct Monitor<
+    #[cfg(any(feature = "stdweb", feature = "web_sys"))] S: GamepadSource = BrowserSource,
+    #[cfg(not(any(feature = "stdweb", feature = "web_sys")))] S: GamepadSource,
+> {
+    source: S,
+    queue: EventQueue,
     pads: Vec<Option<ConnectedPad>>,
+    dead_zones: DeadZones,
+    pad_dead_zones: HashMap<usize, DeadZones>,
+    trigger_threshold: TriggerThreshold,
+    sdl_database: SdlDatabase,
+    mapping_resolver: Option<MappingResolver>,
+    player_filter: Option<Box<dyn FnMut(usize) -> Option<usize>>>,
+    release_on_disconnect: bool,
+    focused: bool,
+    /// The latest snapshot, reused by each update.
+    raw_pads: Vec<Option<(GamepadDescription, GamepadState)>>,
+    /// Connection changes reported by the source, kept while unfocused.
+    connection_changes: Vec<ConnectionChange>,
+    /// Events mapped from one raw event, reused by each mapped poll.
+    mapped: Vec<MappedEvent>,
 }
 
+#[cfg(any(feature = "stdweb", feature = "web_sys"))]
 impl Monitor {
 
     /// Start listening for gamepad connections, disconnections, and input events.
     pub fn new() -> Self {
+        Monitor::with_source(BrowserSource::new())
+    }
+}
+
+impl<S: GamepadSource> Monitor<S> {
+
+    /// Start listening for events from gamepads provided by `source`.
+    pub fn with_source(source: S) -> Self {
         Self {
-            queue: VecDeque::new(),
+            source,
+            queue: EventQueue::default(),
             pads: vec![],
+            dead_zones: DeadZones::default(),
+            pad_dead_zones: HashMap::new(),
+            trigger_threshold: TriggerThreshold::default(),
+            sdl_database: SdlDatabase::bundled(),
+            mapping_resolver: None,
+            player_filter: None,
+            release_on_disconnect: true,
+            focused: true,
+            raw_pads: vec![],
+            connection_changes: vec![],
+            mapped: vec![],
         }
     }
 
-    fn update_state(pad: &mut ConnectedPad, raw: &Gamepad, queue: &mut VecDeque<Event>) {
+    /// Get the gamepad source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get the gamepad source, to change its configuration (or its gamepads, for a test source).
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Set the stick dead zones of every gamepad, except those set by
+    /// [set_pad_dead_zones()](#method.set_pad_dead_zones).
+    ///
+    /// By default, there are no dead zones.
+    /// Stick axes are reported after applying dead zones,
+    /// so no events are emitted while a stick stays within its dead zone.
+    pub fn set_dead_zones(&mut self, zones: DeadZones) {
+        self.dead_zones = zones;
+    }
+
+    /// Set (or with `None`, reset to the default) the stick dead zones of the gamepad at this index.
+    ///
+    /// The setting belongs to the index, so it also applies to any gamepad connected there later.
+    pub fn set_pad_dead_zones(&mut self, index: usize, zones: Option<DeadZones>) {
+        match zones {
+            Some(zones) => self.pad_dead_zones.insert(index, zones),
+            None => self.pad_dead_zones.remove(&index),
+        };
+    }
+
+    /// Get the stick dead zones of the gamepad at this index.
+    pub fn dead_zones(&self, index: usize) -> DeadZones {
+        self.pad_dead_zones.get(&index).cloned().unwrap_or(self.dead_zones)
+    }
+
+    /// Set when [triggers](enum.Trigger.html) are pressed: once their value reaches `press`,
+    /// until it falls below `release`. By default, 0.5 and 0.4.
+    ///
+    /// The gap keeps a trigger held near the threshold from pressing and releasing repeatedly.
+    /// `release` is limited to `press`.
+    /// This applies to triggers read from axes, which get [ButtonPress](enum.MappedEvent.html#variant.ButtonPress)
+    /// and [ButtonRelease](enum.MappedEvent.html#variant.ButtonRelease) mapped events.
+    /// Triggers read from buttons are pressed as the browser reports them.
+    pub fn set_trigger_threshold(&mut self, press: f64, release: f64) {
+        self.trigger_threshold = TriggerThreshold {
+            press,
+            release: release.min(press),
+        };
+    }
+
+    /// Get the database of SDL mappings, used for gamepads without a standard mapping.
+    ///
+    /// By default, this is the [bundled](struct.SdlDatabase.html#method.bundled) database.
+    pub fn sdl_database(&self) -> &SdlDatabase {
+        &self.sdl_database
+    }
+
+    /// Get the database of SDL mappings, to add mappings.
+    ///
+    /// Mappings are looked up when a gamepad connects, so changes apply to gamepads connected afterwards.
+    pub fn sdl_database_mut(&mut self) -> &mut SdlDatabase {
+        &mut self.sdl_database
+    }
+
+    /// Choose a custom [mapping table](struct.MappingTable.html) for each gamepad,
+    /// used in place of its browser and SDL mappings.
+    ///
+    /// `resolver` is given each gamepad's description as it connects (or is
+    /// [reconfigured](enum.EventData.html#variant.Reconfigured)),
+    /// and returns a table, or `None` to keep the gamepad's usual mapping.
+    /// Gamepads already connected keep their mapping.
+    pub fn set_mapping_resolver<F: FnMut(&GamepadDescription) -> Option<MappingTable> + 'static>(&mut self, resolver: F) {
+        self.mapping_resolver = Some(Box::new(resolver));
+    }
+
+    /// Remove the mapping resolver, so gamepads connected afterwards use their usual mapping.
+    pub fn clear_mapping_resolver(&mut self) {
+        self.mapping_resolver = None;
+    }
+
+    /// Choose whether a gamepad's held buttons are released, and its axes returned to 0.0, when it disconnects.
+    ///
+    /// If enabled (the default), the disconnection is preceded by release and axis events,
+    /// so every press is matched by a release.
+    pub fn set_release_on_disconnect(&mut self, enabled: bool) {
+        self.release_on_disconnect = enabled;
+    }
+
+    fn update_state(
+        i: usize,
+        pad: &mut ConnectedPad,
+        next_state: &mut GamepadState,
+        zones: DeadZones,
+        threshold: TriggerThreshold,
+        queue: &mut EventQueue,
+    ) {
 
         // skip update if we already processed this timestamp
-        if pad.state.timestamp() == raw.timestamp() {
+        if pad.state.timestamp() == next_state.timestamp() {
             return;
         }
 
-        let next_state: GamepadState = raw.into();
+        zones.apply_to_state(next_state, pad.desc.as_ref());
+
+        Self::queue_changes(i, pad, next_state, threshold, queue);
+    }
+
+    /// Read the pad's triggers from `state`.
+    fn read_triggers(pad: &ConnectedPad, state: &GamepadState, threshold: TriggerThreshold) -> [TriggerState; 2] {
+        let mut triggers = pad.triggers;
+        for (trigger, &kind) in triggers.iter_mut().zip(TRIGGERS.iter()) {
+            if let Some(input) = pad.desc.button_input(kind.button()) {
+                *trigger = trigger.read(input, state, threshold);
+            }
+        }
+        triggers
+    }
+
+    /// Queue the changes from the pad's stored state to `next_state`, and store it.
+    ///
+    /// The states are swapped, so `next_state` is left holding the previous state, for reuse.
+    fn queue_changes(i: usize, pad: &mut ConnectedPad, next_state: &mut GamepadState, threshold: TriggerThreshold, queue: &mut EventQueue) {
+        let triggers = Self::read_triggers(pad, next_state, threshold);
 
         // queue any changes as events
-        queue.extend( next_state.changes_since(&pad.state)
-            .map(|change| Event::new(pad.desc.clone(), (&change).into()))
-        );
+        let timestamp = next_state.timestamp();
+        for change in next_state.changes_since(&pad.state) {
+            let previous = match change {
+                GamepadStateChange::Axis(index, _) if index < pad.state.axis_count() => Some(pad.state.axis(index)),
+                _ => None,
+            };
+            queue.push_input(pad.desc.clone(), (&change).into(), previous, timestamp);
+        }
+
+        // then each moved stick, with both of its axes
+        for &stick in [Stick::Left, Stick::Right].iter() {
+            let mapping = pad.desc.as_ref();
+            let indices = (mapping.axis_index(stick.x_axis()), mapping.axis_index(stick.y_axis()));
+            if let (Some(x_index), Some(y_index)) = indices {
+                if x_index >= next_state.axis_count() || y_index >= next_state.axis_count()
+                    || x_index >= pad.state.axis_count() || y_index >= pad.state.axis_count() {
+                    continue;
+                }
 
-        pad.state = next_state;
+                let x = next_state.axis(x_index);
+                let y = next_state.axis(y_index);
+                let moved = x.to_bits() != pad.state.axis(x_index).to_bits()
+                    || y.to_bits() != pad.state.axis(y_index).to_bits();
+                if moved {
+                    queue.push_mapped(i, MappedEvent::Stick(stick, x, y), timestamp);
+                }
+            }
+        }
+
+        // then each trigger, pressed here if not read from a button
+        for (&trigger, (previous, next)) in TRIGGERS.iter().zip(pad.triggers.iter().zip(triggers.iter())) {
+            let from_button = matches!(pad.desc.button_input(trigger.button()), Some(SdlInput::Button(_)));
+            if next.pressed != previous.pressed && !from_button {
+                let button = trigger.button();
+                queue.push_mapped(i, if next.pressed { MappedEvent::ButtonPress(button) } else { MappedEvent::ButtonRelease(button) }, timestamp);
+            }
+            if next.value.to_bits() != previous.value.to_bits() {
+                queue.push_mapped(i, MappedEvent::Axis(trigger.axis(), next.value), timestamp);
+            }
+        }
+        pad.triggers = triggers;
+
+        mem::swap(&mut pad.state, next_state);
     }
 
     /// Update our stored snapshot from given Pad state. Enqueue any changes.
-    fn update_pad(&mut self, i: usize, raw: &Gamepad) {
+    fn update_pad(&mut self, i: usize, desc: &GamepadDescription, state: &mut GamepadState) {
 
+        let zones = self.dead_zones(i);
+        let threshold = self.trigger_threshold;
         let queue = &mut self.queue;
+        let sdl_database = &self.sdl_database;
+        let resolver = &mut self.mapping_resolver;
+
+        let pad = self.pads[i].get_or_insert_with(|| Self::make_connected(desc.clone(), state.timestamp(), sdl_database, resolver, queue));
+        if Self::layout_changed(&pad.desc, desc) {
+            Self::reconfigure_pad(i, pad, desc.clone(), sdl_database, resolver, threshold, queue);
+        }
+        Self::update_state(i, pad, state, zones, threshold, queue);
+    }
+
+    /// Did the gamepad's number of axes or buttons, or its mapping, change since we stored its description?
+    fn layout_changed(stored: &GamepadDescription, desc: &GamepadDescription) -> bool {
+        stored.axis_count != desc.axis_count
+            || stored.button_count != desc.button_count
+            || stored.mapping != desc.mapping
+    }
 
-        let pad = self.pads[i].get_or_insert_with(|| Monitor::make_connected(&raw, queue));
-        Monitor::update_state(pad, raw, queue);
+    /// Switch the pad to a new layout, and add a reconfigured event to queue.
+    ///
+    /// Inputs the pad no longer has are released first.
+    /// If the mapping changed, every input is released, since its meaning may have changed.
+    fn reconfigure_pad(
+        i: usize,
+        pad: &mut ConnectedPad,
+        desc: GamepadDescription,
+        sdl_database: &SdlDatabase,
+        resolver: &mut Option<MappingResolver>,
+        threshold: TriggerThreshold,
+        queue: &mut EventQueue,
+    ) {
+        let (axis_count, button_count) = (pad.state.axis_count(), pad.state.button_count());
+        let mut remaining = pad.state.clone();
+        if pad.desc.mapping == desc.mapping {
+            remaining.resize(desc.axis_count.min(axis_count), desc.button_count.min(button_count));
+        } else {
+            remaining.resize(0, 0);
+        }
+        remaining.resize(axis_count, button_count);
+        Self::queue_changes(i, pad, &mut remaining, threshold, queue);
+
+        pad.state.resize(desc.axis_count, desc.button_count);
+        pad.desc = Rc::new(Self::resolve_mapping(desc, sdl_database, resolver));
+        queue.push_raw(pad.desc.clone(), EventData::Reconfigured, pad.state.timestamp());
+    }
+
+    /// Queue releases of the pad's held buttons, and return its axes to rest:
+    /// 0.0, or the released end of a trigger's axis.
+    fn release_pad(i: usize, pad: &mut ConnectedPad, threshold: TriggerThreshold, queue: &mut EventQueue) {
+        let mut neutral: GamepadState = pad.desc.as_ref().into();
+        neutral.set_timestamp(pad.state.timestamp());
+        for &trigger in TRIGGERS.iter() {
+            if let Some(SdlInput::Axis { index, half: None, inverted }) = pad.desc.button_input(trigger.button()) {
+                if index < neutral.axis_count() {
+                    neutral.set_axis(index, if inverted { 1.0 } else { -1.0 });
+                }
+            }
+        }
+        Self::queue_changes(i, pad, &mut neutral, threshold, queue);
+        for trigger in pad.triggers.iter_mut() {
+            *trigger = trigger.untouched();
+        }
     }
 
-    /// Reset the pad to None and emit a disconnected event.
+    /// Reset the pad to None and emit a disconnected event,
+    /// after releasing its input if enabled.
     /// Does nothing if pad is already disconnected.
     fn disconnect_pad(&mut self, i: usize) {
-        if let Some(pad) = self.pads[i].take() {
-            self.queue.push_back(Event::new(pad.desc, EventData::Disconnected));
+        if let Some(mut pad) = self.pads[i].take() {
+            if self.release_on_disconnect {
+                Self::release_pad(i, &mut pad, self.trigger_threshold, &mut self.queue);
+            }
+            self.queue.push_raw(pad.desc, EventData::Disconnected, pad.state.timestamp());
         }
     }
 
-    /// Creates a ConnectedPad and adds a connected event to queue
-    fn make_connected(raw: &Gamepad, queue: &mut VecDeque<Event>) -> ConnectedPad {
-        let desc: GamepadDescription = raw.into();
-        let pad: ConnectedPad = desc.into();
-        queue.push_back(Event::new(pad.desc.clone(), EventData::Connected));
+    /// Gamepads get a mapping table from the resolver, if it has one for them.
+    /// Otherwise, gamepads without a known mapping get an SDL mapping, if one is found.
+    fn resolve_mapping(mut desc: GamepadDescription, sdl_database: &SdlDatabase, resolver: &mut Option<MappingResolver>) -> GamepadDescription {
+        if desc.mapping_table.is_none() {
+            desc.mapping_table = resolver.as_mut().and_then(|resolver| resolver(&desc)).map(Rc::new);
+        }
+        if desc.mapping_table.is_none() && desc.mapping == GamepadMappingType::NoMapping && desc.sdl_mapping.is_none() {
+            desc.sdl_mapping = sdl_database.lookup(&desc).cloned();
+        }
+        desc
+    }
+
+    /// Creates a ConnectedPad and adds a connected event to queue.
+    fn make_connected(
+        desc: GamepadDescription,
+        timestamp: f64,
+        sdl_database: &SdlDatabase,
+        resolver: &mut Option<MappingResolver>,
+        queue: &mut EventQueue,
+    ) -> ConnectedPad {
+        let pad: ConnectedPad = Self::resolve_mapping(desc, sdl_database, resolver).into();
+        queue.push_raw(pad.desc.clone(), EventData::Connected, timestamp);
         pad
     }
 
@@ -95,8 +471,9 @@ impl Monitor {
         let orig_size = self.pads.len();
 
         // grow to handle more gamepads than before
-        let extra_pads = iter::repeat(None).take(size - orig_size);
-        self.pads.extend(extra_pads);
+        if size > orig_size {
+            self.pads.resize_with(size, || None);
+        }
 
         // shrink to handle fewer gamepads than before
         for i in size..orig_size {
@@ -107,38 +484,266 @@ impl Monitor {
 
     fn fetch_update(&mut self) {
 
-        // navigator.getGamepads() MUST be called each update.
-        // Chrome only updates Gamepad state in get_gamepads()
-        // (Counter to MDN documentation, which indicates we can save Gamepad references)
-        let raw_pads = Gamepad::get_all();
+        let focus = self.source.focus();
+        let connection_changes = self.source.connection_changes();
+
+        // reuse the previous snapshot's buffers, moved out while the pads are updated
+        let mut raw_pads = mem::take(&mut self.raw_pads);
+        self.source.snapshot_into(&mut raw_pads);
+        self.apply_snapshot(focus, connection_changes, &mut raw_pads);
+        self.raw_pads = raw_pads;
+    }
+
+    fn apply_snapshot(
+        &mut self,
+        focus: FocusState,
+        connection_changes: Vec<ConnectionChange>,
+        raw_pads: &mut [Option<(GamepadDescription, GamepadState)>],
+    ) {
+        if focus != FocusState::Focused && self.focused {
+            self.lose_focus();
+        }
+
+        // connections are applied once focused, in order
+        self.connection_changes.extend(connection_changes);
+        if focus == FocusState::Unfocused {
+            return;
+        }
+        let mut changes = mem::take(&mut self.connection_changes);
+        for change in changes.drain(..) {
+            self.apply_connection_change(change);
+        }
+        self.connection_changes = changes;
+
+        if focus == FocusState::Refocused || !self.focused {
+            return self.regain_focus(raw_pads);
+        }
 
         self.resize_pads(raw_pads.len());
 
         // update snapshots for each pad, enqueing any changes
-        for (i, raw) in raw_pads.iter().enumerate() {
-            match raw {
+        for (i, raw) in raw_pads.iter_mut().enumerate() {
+            match *raw {
                 None => self.disconnect_pad(i),
-                Some(raw) => self.update_pad(i, &raw),
+                Some((ref desc, ref mut state)) => self.update_pad(i, desc, state),
             }
         }
     }
 
+    /// Connect or disconnect a pad as reported by the source, unless the change was already seen.
+    fn apply_connection_change(&mut self, change: ConnectionChange) {
+        match change {
+            ConnectionChange::Connected(desc, mut state) => {
+                if desc.index < 0 {
+                    return;
+                }
+                let i = desc.index as usize;
+                if i >= self.pads.len() {
+                    self.resize_pads(i + 1);
+                }
+                if self.pads[i].is_none() {
+                    self.update_pad(i, &desc, &mut state);
+                }
+            },
+            ConnectionChange::Disconnected(i) => {
+                if i < self.pads.len() {
+                    self.disconnect_pad(i);
+                }
+            },
+        }
+    }
+
+    /// Release every pad's input, and pause until focus returns.
+    fn lose_focus(&mut self) {
+        self.focused = false;
+        let threshold = self.trigger_threshold;
+        for (i, pad) in self.pads.iter_mut().enumerate() {
+            if let Some(ref mut pad) = *pad {
+                Self::release_pad(i, pad, threshold, &mut self.queue);
+                self.queue.push_raw(pad.desc.clone(), EventData::FocusLost, pad.state.timestamp());
+            }
+        }
+    }
+
+    /// Take each connected pad's state from `raw_pads`, without emitting input events.
+    /// Pads connected, disconnected, replaced, or reconfigured meanwhile are reported as usual.
+    fn regain_focus(&mut self, raw_pads: &mut [Option<(GamepadDescription, GamepadState)>]) {
+        self.focused = true;
+        self.resize_pads(raw_pads.len());
+
+        for (i, raw) in raw_pads.iter_mut().enumerate() {
+            // a different gamepad in the slot is a new connection
+            let replaced = match (&*raw, &self.pads[i]) {
+                (Some((desc, _)), Some(pad)) => pad.desc.name != desc.name,
+                _ => false,
+            };
+            if replaced {
+                self.disconnect_pad(i);
+            }
+
+            match (raw, self.pads[i].is_some()) {
+                (&mut None, _) => self.disconnect_pad(i),
+                (&mut Some((ref desc, ref mut state)), true) => {
+                    let zones = self.dead_zones(i);
+                    let threshold = self.trigger_threshold;
+                    if let Some(ref mut pad) = self.pads[i] {
+                        if Self::layout_changed(&pad.desc, desc) {
+                            Self::reconfigure_pad(i, pad, desc.clone(), &self.sdl_database, &mut self.mapping_resolver, threshold, &mut self.queue);
+                        }
+                        zones.apply_to_state(state, pad.desc.as_ref());
+                        pad.triggers = Self::read_triggers(pad, state, threshold);
+                        mem::swap(&mut pad.state, state);
+                        self.queue.push_raw(pad.desc.clone(), EventData::FocusGained, pad.state.timestamp());
+                    }
+                },
+                (&mut Some((ref desc, ref mut state)), false) => self.update_pad(i, desc, state),
+            }
+        }
+    }
+
+    fn next_queued(&mut self) -> Option<Queued> {
+        if self.queue.queue.is_empty() {
+            self.fetch_update();
+        }
+
+        self.queue.queue.pop_front()
+    }
+
     /// Get the next event.
     pub fn poll(&mut self) -> Option<Event> {
-        if self.queue.is_empty() {
-            self.fetch_update();
+        while let Some(queued) = self.next_queued() {
+            if let Queued::Raw(event, _) = queued {
+                return Some(event)
+            }
         }
+        None
+    }
 
-        self.queue.pop_front()
+    /// Append every pending event to `events`, like calling [poll()](#method.poll) until it returns `None`.
+    ///
+    /// Reusing the same buffer each frame avoids allocating.
+    pub fn poll_into(&mut self, events: &mut Vec<Event>) {
+        while let Some(event) = self.poll() {
+            events.push(event);
+        }
     }
 
     /// Get the next input event, discard other events.
     ///
     /// This is best suited for a single-user interface, because it merges input from all connected gamepads.
     pub fn poll_mapped(&mut self) -> Option<MappedEvent> {
-        while let Some(event) = self.poll() {
-            if let Some(mapped) = event.map() {
-                return Some(mapped)
+        self.poll_pad_mapped().map(|mapped| mapped.event)
+    }
+
+    /// Append every pending input event to `events`,
+    /// like calling [poll_mapped()](#method.poll_mapped) until it returns `None`.
+    pub fn poll_mapped_into(&mut self, events: &mut Vec<MappedEvent>) {
+        while let Some(event) = self.poll_mapped() {
+            events.push(event);
+        }
+    }
+
+    /// Map a raw event taken from `position` in the queue, and return its first input event.
+    /// Any further input events are queued in its place.
+    fn map_taken(&mut self, event: Event, previous: Option<f64>, position: usize) -> Option<PadMappedEvent> {
+        let mut mapped = mem::take(&mut self.mapped);
+        mapped.clear();
+        event.map_each_since(previous, |event| mapped.push(event));
+
+        let (pad, timestamp, sequence) = (event.gamepad.index as usize, event.timestamp, event.sequence);
+        let with_pad = |mapped| PadMappedEvent { pad, event: mapped, timestamp, sequence };
+        for (offset, &extra) in mapped.iter().skip(1).enumerate() {
+            self.queue.queue.insert(position + offset, Queued::Mapped(with_pad(extra)));
+        }
+        let first = mapped.first().map(|&first| with_pad(first));
+
+        self.mapped = mapped;
+        first
+    }
+
+    /// Get the next input event, with its gamepad and timestamp. Discard other events.
+    pub fn poll_pad_mapped(&mut self) -> Option<PadMappedEvent> {
+        self.poll_pad_mapped_with(|_| ())
+    }
+
+    /// Like [poll_pad_mapped()](#method.poll_pad_mapped), but pass each raw event to `on_raw` before mapping it.
+    pub(crate) fn poll_pad_mapped_with<F: FnMut(&Event)>(&mut self, mut on_raw: F) -> Option<PadMappedEvent> {
+        while let Some(queued) = self.next_queued() {
+            match queued {
+                Queued::Raw(event, previous) => {
+                    on_raw(&event);
+                    if let Some(mapped) = self.map_taken(event, previous, 0) {
+                        return Some(mapped)
+                    }
+                },
+                Queued::Mapped(mapped) =>
+                    return Some(mapped),
+            }
+        }
+        None
+    }
+
+    /// Remove the next queued input event of this gamepad, discarding its other events.
+    fn take_mapped_for(&mut self, pad: usize) -> Option<PadMappedEvent> {
+        while let Some(position) = self.queue.queue.iter().position(|queued| queued.pad() == pad) {
+            match self.queue.queue.remove(position) {
+                Some(Queued::Raw(event, previous)) =>
+                    if let Some(mapped) = self.map_taken(event, previous, position) {
+                        return Some(mapped)
+                    },
+                Some(Queued::Mapped(mapped)) =>
+                    return Some(mapped),
+                None => (),
+            }
+        }
+        None
+    }
+
+    /// Get the next input event of the gamepad at this index, discard its other events.
+    ///
+    /// Events of other gamepads stay queued, for their own `poll_mapped_for()`, or any other poll.
+    /// Poll every gamepad, or their events will accumulate, up to 1024 events,
+    /// after which the oldest are dropped;
+    /// [poll_player_mapped()](#method.poll_player_mapped) routes all events instead.
+    pub fn poll_mapped_for(&mut self, pad: usize) -> Option<MappedEvent> {
+        if let Some(mapped) = self.take_mapped_for(pad) {
+            return Some(mapped.event);
+        }
+        // only other gamepads' events are left, so drop the oldest if nobody polls them
+        if self.queue.queue.len() > MAX_QUEUED {
+            let excess = self.queue.queue.len() - MAX_QUEUED;
+            self.queue.queue.drain(..excess);
+        }
+        self.fetch_update();
+        self.take_mapped_for(pad).map(|mapped| mapped.event)
+    }
+
+    /// Choose which player receives each gamepad's events, from
+    /// [poll_player_mapped()](#method.poll_player_mapped).
+    ///
+    /// `filter` is given a gamepad index, and returns a player number,
+    /// or `None` to discard the gamepad's events.
+    /// By default, each gamepad is the player with the same number.
+    pub fn set_player_filter<F: FnMut(usize) -> Option<usize> + 'static>(&mut self, filter: F) {
+        self.player_filter = Some(Box::new(filter));
+    }
+
+    /// Reset the player filter, so each gamepad is the player with the same number.
+    pub fn clear_player_filter(&mut self) {
+        self.player_filter = None;
+    }
+
+    /// Get the next input event, with the player it belongs to. Discard other events.
+    ///
+    /// Players are chosen by the [player filter](#method.set_player_filter).
+    pub fn poll_player_mapped(&mut self) -> Option<PlayerMappedEvent> {
+        while let Some(mapped) = self.poll_pad_mapped() {
+            let player = match self.player_filter {
+                Some(ref mut filter) => filter(mapped.pad),
+                None => Some(mapped.pad),
+            };
+            if let Some(player) = player {
+                return Some(PlayerMappedEvent { player, event: mapped.event });
             }
         }
         None
@@ -160,9 +765,726 @@ impl Monitor {
 
     /// Get the latest polled state of the gamepad connected at this index.
     ///
+    /// Stick axes are reported after applying [dead zones](#method.set_dead_zones),
+    /// and trigger buttons pressed by the [trigger threshold](#method.set_trigger_threshold).
     /// This does not process new events; it returns a snapshot from the most recent poll.
     /// Only [poll()](#method.poll) and [poll_mapped()](#method.poll_mapped) process events.
     pub fn pad_state(&self, index: usize) -> Option<&GamepadState> {
         self.pad(index).map(|pad| &pad.state)
     }
+
+    /// Check that the gamepad at this index is connected, and can rumble.
+    fn rumble_pad(&self, index: usize) -> Result<(), RumbleError> {
+        match self.pad_description(index) {
+            None => Err(RumbleError::NotConnected),
+            Some(desc) if !desc.rumble.any() => Err(RumbleError::Unsupported),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Play a rumble effect on the gamepad at this index, replacing any effect it is playing.
+    ///
+    /// The effect is played with the motors the gamepad has, as reported by its
+    /// [rumble capabilities](struct.GamepadDescription.html#structfield.rumble):
+    /// trigger magnitudes are dropped without trigger rumble,
+    /// and a single motor plays the [strongest](struct.RumbleEffect.html#method.magnitude) magnitude.
+    /// For timed sequences of effects, use a [RumbleSequencer](struct.RumbleSequencer.html).
+    pub fn rumble(&mut self, index: usize, effect: RumbleEffect) -> Result<(), RumbleError> {
+        self.rumble_pad(index)?;
+        self.source.rumble(index, &effect)
+    }
+
+    /// Stop any rumble effect playing on the gamepad at this index.
+    pub fn stop_rumble(&mut self, index: usize) -> Result<(), RumbleError> {
+        self.rumble_pad(index)?;
+        self.source.stop_rumble(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::deadzone::{
+        DeadZone,
+        DeadZoneShape,
+    };
+    use ::mapping::{
+        Axis,
+        Button,
+    };
+    use ::mock::MockSource;
+    use ::sdl::{
+        SdlInput,
+        SdlTarget,
+    };
+    use std::iter;
+
+    fn standard_pad(index: i32) -> GamepadDescription {
+        GamepadDescription::new(index, format!("pad {}", index), GamepadMappingType::Standard, 4, 17)
+    }
+
+    fn mock_monitor() -> Monitor<MockSource> {
+        Monitor::with_source(MockSource::new())
+    }
+
+    /// Poll until empty, returning (pad index, data) of each event.
+    fn drain(monitor: &mut Monitor<MockSource>) -> Vec<(i32, EventData)> {
+        let mut events = vec![];
+        while let Some(event) = monitor.poll() {
+            events.push((event.gamepad.index, event.data));
+        }
+        events
+    }
+
+    #[test]
+    fn test_monitor_connect_input_disconnect() {
+        let mut monitor = mock_monitor();
+        assert_eq!(drain(&mut monitor), vec![]);
+
+        monitor.source_mut().connect(standard_pad(0));
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Connected)]);
+        assert_eq!(monitor.pad_description(0), Some(&standard_pad(0)));
+
+        monitor.source_mut().set_axis(0, 1, 0.5);
+        monitor.source_mut().press(0, 3);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Axis(1, 0.5)),
+            (0, EventData::Button(3, true)),
+            (0, EventData::ButtonValue(3, 1.0)),
+        ]);
+        assert_eq!(monitor.pad_state(0).unwrap().axis(1), 0.5);
+
+        monitor.source_mut().release(0, 3);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Button(3, false)),
+            (0, EventData::ButtonValue(3, 0.0)),
+        ]);
+
+        // the moved axis is returned to 0.0
+        monitor.source_mut().disconnect(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Axis(1, 0.0)), (0, EventData::Disconnected)]);
+        assert_eq!(monitor.pad_description(0), None);
+        assert_eq!(monitor.pad_state(0), None);
+    }
+
+    #[test]
+    fn test_monitor_skips_unchanged_timestamp() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        drain(&mut monitor);
+
+        monitor.source_mut().set_axis(0, 0, -1.0);
+        assert_eq!(drain(&mut monitor), vec![]);
+
+        monitor.source_mut().set_timestamp(0, 10.0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Axis(0, -1.0))]);
+        assert_eq!(monitor.pad_state(0).unwrap().timestamp(), 10.0);
+    }
+
+    #[test]
+    fn test_monitor_resize_pads() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(standard_pad(2));
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Connected),
+            (2, EventData::Connected),
+        ]);
+        assert_eq!(monitor.pad_description(1), None);
+
+        // shrinking the array drops the pads beyond it
+        monitor.source_mut().resize(1);
+        assert_eq!(drain(&mut monitor), vec![(2, EventData::Disconnected)]);
+
+        monitor.source_mut().resize(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Disconnected)]);
+        assert_eq!(monitor.pad_description(0), None);
+
+        // an index may be reused by a different gamepad
+        monitor.source_mut().connect(GamepadDescription::new(0, "other".into(), GamepadMappingType::NoMapping, 2, 2));
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Connected)]);
+        assert_eq!(monitor.pad_description(0).unwrap().name, "other");
+    }
+
+    #[test]
+    fn test_monitor_dead_zones() {
+        let mut monitor = mock_monitor();
+        monitor.set_dead_zones(DeadZones::both(DeadZone::new(DeadZoneShape::ScaledRadial, 0.25, 1.0)));
+        monitor.set_pad_dead_zones(1, Some(DeadZones::default()));
+        monitor.source_mut().connect(GamepadDescription::new(0, "pad 0".into(), GamepadMappingType::Standard, 5, 17));
+        monitor.source_mut().connect(standard_pad(1));
+        drain(&mut monitor);
+
+        // no events while the stick stays within its dead zone
+        monitor.source_mut().set_axis(0, 0, 0.1);
+        monitor.source_mut().set_axis(0, 1, -0.1);
+        monitor.source_mut().bump_timestamp(0);
+        monitor.source_mut().set_axis(1, 0, 0.1);
+        monitor.source_mut().bump_timestamp(1);
+        assert_eq!(drain(&mut monitor), vec![(1, EventData::Axis(0, 0.1))]);
+
+        // both axes are processed together
+        monitor.source_mut().set_axis(0, 0, 0.625);
+        monitor.source_mut().set_axis(0, 1, 0.0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Axis(0, 0.5))]);
+
+        monitor.source_mut().set_axis(0, 0, 0.0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Axis(0, 0.0))]);
+        assert_eq!(monitor.pad_state(0).unwrap().axis(0), 0.0);
+
+        // the right stick has its own dead zone
+        monitor.source_mut().set_axis(0, 2, 0.1);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![]);
+
+        // unmapped axes are left alone
+        monitor.source_mut().set_axis(0, 4, 0.1);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Axis(4, 0.1))]);
+    }
+
+    #[test]
+    fn test_monitor_poll_mapped() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(GamepadDescription::new(1, "unmapped".into(), GamepadMappingType::NoMapping, 4, 17));
+
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().bump_timestamp(0);
+        monitor.source_mut().press(1, 0);
+        monitor.source_mut().bump_timestamp(1);
+
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonPress(Button::South)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonValue(Button::South, 1.0)));
+        assert_eq!(monitor.poll_mapped(), None);
+    }
+
+    #[test]
+    fn test_monitor_stick_events() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        drain(&mut monitor);
+
+        // one stick event, after both axes
+        monitor.source_mut().set_axis(0, 0, 0.5);
+        monitor.source_mut().set_axis(0, 1, -0.25);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Axis(Axis::LeftStickX, 0.5)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Axis(Axis::LeftStickY, -0.25)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Stick(Stick::Left, 0.5, -0.25)));
+        assert_eq!(monitor.poll_mapped(), None);
+
+        // the unchanged axis keeps its value
+        monitor.source_mut().set_axis(0, 1, 0.0);
+        monitor.source_mut().set_axis(0, 3, 1.0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Axis(Axis::LeftStickY, 0.0)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Axis(Axis::RightStickY, 1.0)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Stick(Stick::Left, 0.5, 0.0)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Stick(Stick::Right, 0.0, 1.0)));
+        assert_eq!(monitor.poll_mapped(), None);
+
+        // raw polling skips stick events
+        monitor.source_mut().set_axis(0, 2, 1.0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Axis(2, 1.0))]);
+    }
+
+    #[test]
+    fn test_monitor_sdl_mapping() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(GamepadDescription::new(0, "045e-028e-Microsoft X-Box 360 pad".into(), GamepadMappingType::NoMapping, 8, 11));
+        monitor.source_mut().connect(GamepadDescription::new(1, "1234-5678-Unknown pad".into(), GamepadMappingType::NoMapping, 2, 4));
+        drain(&mut monitor);
+
+        assert!(monitor.pad_description(0).unwrap().sdl_mapping.is_some());
+        assert!(monitor.pad_description(1).unwrap().sdl_mapping.is_none());
+
+        // Xbox 360 on Linux: "start" is button 7, the right stick is axes 3 and 4
+        monitor.source_mut().press(0, 7);
+        monitor.source_mut().set_axis(0, 4, 0.5);
+        monitor.source_mut().set_axis(0, 5, 1.0);
+        monitor.source_mut().bump_timestamp(0);
+        monitor.source_mut().press(1, 0);
+        monitor.source_mut().bump_timestamp(1);
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Axis(Axis::RightStickY, 0.5)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonValue(Button::RT2, 1.0)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonPress(Button::Start)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonValue(Button::Start, 1.0)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Stick(Stick::Right, 0.0, 0.5)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonPress(Button::RT2)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::Axis(Axis::RightTrigger, 1.0)));
+        assert_eq!(monitor.poll_mapped(), None);
+
+        // an empty database maps nothing
+        let mut monitor = mock_monitor();
+        *monitor.sdl_database_mut() = SdlDatabase::new();
+        monitor.source_mut().connect(GamepadDescription::new(0, "045e-028e-Microsoft X-Box 360 pad".into(), GamepadMappingType::NoMapping, 8, 11));
+        drain(&mut monitor);
+        assert!(monitor.pad_description(0).unwrap().sdl_mapping.is_none());
+    }
+
+    #[test]
+    fn test_monitor_mapping_table() {
+        let mut table = MappingTable::new();
+        table.bind(SdlTarget::Button(Button::South), SdlInput::Button(3));
+        table.bind(SdlTarget::Axis(Axis::LeftStickX, None), SdlInput::Axis { index: 1, half: None, inverted: false });
+        table.bind_hat(0);
+
+        let mut monitor = mock_monitor();
+        monitor.set_mapping_resolver(move |desc| if desc.name.contains("Unknown") { Some(table.clone()) } else { None });
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(GamepadDescription::new(1, "045e-028e-Unknown pad".into(), GamepadMappingType::NoMapping, 2, 4));
+        monitor.source_mut().set_axis(1, 0, 9.0 / 7.0);
+        drain(&mut monitor);
+
+        assert!(monitor.pad_description(0).unwrap().mapping_table.is_none());
+        assert!(monitor.pad_description(1).unwrap().sdl_mapping.is_none());
+        assert_eq!(monitor.pad_description(1).unwrap().button_index(Button::South), Some(3));
+
+        // a hat switch maps to several events, which stay ahead of later events,
+        // and only the directions which changed are mapped
+        monitor.source_mut().set_axis(1, 0, 1.0);
+        monitor.source_mut().press(1, 3);
+        monitor.source_mut().bump_timestamp(1);
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(monitor.poll_mapped_for(1), Some(MappedEvent::ButtonPress(Button::Up)));
+        assert_eq!(monitor.poll_mapped(), Some(MappedEvent::ButtonPress(Button::South)));
+        let mapped: Vec<_> = iter::from_fn(|| monitor.poll_mapped_for(1)).collect();
+        assert_eq!(mapped, vec![
+            MappedEvent::ButtonValue(Button::Up, 1.0),
+            MappedEvent::ButtonPress(Button::Left),
+            MappedEvent::ButtonValue(Button::Left, 1.0),
+            MappedEvent::ButtonPress(Button::South),
+            MappedEvent::ButtonValue(Button::South, 1.0),
+        ]);
+
+        // gamepads connected after the resolver is cleared keep their usual mapping
+        monitor.clear_mapping_resolver();
+        monitor.source_mut().disconnect(1);
+        drain(&mut monitor);
+        monitor.source_mut().connect(GamepadDescription::new(1, "045e-028e-Unknown pad".into(), GamepadMappingType::NoMapping, 2, 4));
+        drain(&mut monitor);
+        assert!(monitor.pad_description(1).unwrap().mapping_table.is_none());
+    }
+
+    #[test]
+    fn test_monitor_triggers() {
+        let mut table = MappingTable::new();
+        table.bind(SdlTarget::Button(Button::RT2), SdlInput::Axis { index: 2, half: None, inverted: false });
+
+        let mut monitor = mock_monitor();
+        monitor.set_mapping_resolver(move |desc| if desc.index == 1 { Some(table.clone()) } else { None });
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(GamepadDescription::new(1, "unmapped".into(), GamepadMappingType::NoMapping, 4, 4));
+        drain(&mut monitor);
+
+        // a trigger read from a button keeps the browser's pressed state
+        let script = |monitor: &mut Monitor<MockSource>, pressed, value| {
+            monitor.source_mut().set_button(0, 6, pressed, value);
+            monitor.source_mut().bump_timestamp(0);
+            iter::from_fn(|| monitor.poll_mapped()).collect::<Vec<_>>()
+        };
+        assert_eq!(script(&mut monitor, true, 0.3), vec![
+            MappedEvent::ButtonPress(Button::LT2),
+            MappedEvent::ButtonValue(Button::LT2, 0.3),
+            MappedEvent::Axis(Axis::LeftTrigger, 0.3),
+        ]);
+        assert_eq!(script(&mut monitor, false, 0.6), vec![
+            MappedEvent::ButtonRelease(Button::LT2),
+            MappedEvent::ButtonValue(Button::LT2, 0.6),
+            MappedEvent::Axis(Axis::LeftTrigger, 0.6),
+        ]);
+        assert!(!monitor.pad_state(0).unwrap().button_pressed(6));
+
+        // a trigger read from an axis, which reports 0.0 until first touched, then rests at -1.0
+        let script = |monitor: &mut Monitor<MockSource>, value| {
+            monitor.source_mut().set_axis(1, 2, value);
+            monitor.source_mut().bump_timestamp(1);
+            iter::from_fn(|| monitor.poll_mapped()).collect::<Vec<_>>()
+        };
+        assert_eq!(script(&mut monitor, -1.0), vec![
+            MappedEvent::ButtonValue(Button::RT2, 0.0),
+        ]);
+        assert_eq!(script(&mut monitor, 1.0), vec![
+            MappedEvent::ButtonValue(Button::RT2, 1.0),
+            MappedEvent::ButtonPress(Button::RT2),
+            MappedEvent::Axis(Axis::RightTrigger, 1.0),
+        ]);
+
+        // pressed and released with hysteresis
+        assert_eq!(script(&mut monitor, -0.1), vec![
+            MappedEvent::ButtonValue(Button::RT2, 0.45),
+            MappedEvent::Axis(Axis::RightTrigger, 0.45),
+        ]);
+        assert_eq!(script(&mut monitor, -0.5), vec![
+            MappedEvent::ButtonValue(Button::RT2, 0.25),
+            MappedEvent::ButtonRelease(Button::RT2),
+            MappedEvent::Axis(Axis::RightTrigger, 0.25),
+        ]);
+        monitor.set_trigger_threshold(0.2, 0.1);
+        assert_eq!(script(&mut monitor, -0.6), vec![
+            MappedEvent::ButtonValue(Button::RT2, 0.2),
+            MappedEvent::ButtonPress(Button::RT2),
+            MappedEvent::Axis(Axis::RightTrigger, 0.2),
+        ]);
+
+        // released on disconnect
+        monitor.source_mut().disconnect(1);
+        assert_eq!(iter::from_fn(|| monitor.poll_mapped()).collect::<Vec<_>>(), vec![
+            MappedEvent::ButtonValue(Button::RT2, 0.0),
+            MappedEvent::ButtonRelease(Button::RT2),
+            MappedEvent::Axis(Axis::RightTrigger, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_monitor_timestamps() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(standard_pad(1));
+        monitor.source_mut().set_timestamp(1, 5.0);
+
+        let connected: Vec<_> = iter::from_fn(|| monitor.poll()).map(|event| (event.timestamp, event.sequence)).collect();
+        assert_eq!(connected, vec![(0.0, 0), (5.0, 1)]);
+
+        monitor.source_mut().press(1, 0);
+        monitor.source_mut().set_timestamp(1, 10.0);
+        monitor.source_mut().set_axis(0, 0, 0.5);
+        monitor.source_mut().set_timestamp(0, 12.0);
+        let mapped: Vec<_> = iter::from_fn(|| monitor.poll_pad_mapped()).collect();
+        assert_eq!(mapped, vec![
+            PadMappedEvent { pad: 0, event: MappedEvent::Axis(Axis::LeftStickX, 0.5), timestamp: 12.0, sequence: 2 },
+            PadMappedEvent { pad: 0, event: MappedEvent::Stick(Stick::Left, 0.5, 0.0), timestamp: 12.0, sequence: 3 },
+            PadMappedEvent { pad: 1, event: MappedEvent::ButtonPress(Button::South), timestamp: 10.0, sequence: 4 },
+            PadMappedEvent { pad: 1, event: MappedEvent::ButtonValue(Button::South, 1.0), timestamp: 10.0, sequence: 5 },
+        ]);
+
+        monitor.source_mut().disconnect(1);
+        let released: Vec<_> = iter::from_fn(|| monitor.poll()).map(|event| (event.data, event.timestamp, event.sequence)).collect();
+        assert_eq!(released, vec![
+            (EventData::Button(0, false), 10.0, 6),
+            (EventData::ButtonValue(0, 0.0), 10.0, 7),
+            (EventData::Disconnected, 10.0, 8),
+        ]);
+    }
+
+    #[test]
+    fn test_monitor_poll_mapped_for() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(standard_pad(1));
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().press(1, 1);
+
+        assert_eq!(monitor.poll_mapped_for(1), Some(MappedEvent::ButtonPress(Button::East)));
+        assert_eq!(monitor.poll_mapped_for(1), Some(MappedEvent::ButtonValue(Button::East, 1.0)));
+        assert_eq!(monitor.poll_mapped_for(1), None);
+        assert_eq!(monitor.poll_mapped_for(2), None);
+
+        // pad 0's events are still queued, but pad 1's connection was discarded
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Connected),
+            (0, EventData::Button(0, true)),
+            (0, EventData::ButtonValue(0, 1.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_monitor_poll_mapped_for_one_pad() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(standard_pad(1));
+
+        // only pad 1 is polled, while both move every frame
+        for frame in 0..5000 {
+            let value = if frame % 2 == 0 { 0.5 } else { -0.5 };
+            monitor.source_mut().set_axis(0, 0, value);
+            monitor.source_mut().bump_timestamp(0);
+            monitor.source_mut().set_axis(1, 0, value);
+            monitor.source_mut().bump_timestamp(1);
+            assert_eq!(monitor.poll_mapped_for(1), Some(MappedEvent::Axis(Axis::LeftStickX, value)));
+            assert_eq!(monitor.poll_mapped_for(1), Some(MappedEvent::Stick(Stick::Left, value, 0.0)));
+            assert_eq!(monitor.poll_mapped_for(1), None);
+            assert!(monitor.queue.queue.len() <= MAX_QUEUED + 10);
+        }
+    }
+
+    #[test]
+    fn test_monitor_poll_player_mapped() {
+        let mut monitor = mock_monitor();
+        for i in 0..3 {
+            monitor.source_mut().connect(standard_pad(i));
+            monitor.source_mut().press(i as usize, 0);
+        }
+
+        // pads 2 and 0 play, in that order
+        monitor.set_player_filter(|pad| match pad {
+            2 => Some(0),
+            0 => Some(1),
+            _ => None,
+        });
+        let players: Vec<_> = iter::from_fn(|| monitor.poll_player_mapped()).map(|mapped| mapped.player).collect();
+        assert_eq!(players, vec![1, 1, 0, 0]);
+
+        monitor.clear_player_filter();
+        monitor.source_mut().release(1, 0);
+        monitor.source_mut().bump_timestamp(1);
+        assert_eq!(monitor.poll_player_mapped(), Some(PlayerMappedEvent { player: 1, event: MappedEvent::ButtonRelease(Button::South) }));
+    }
+
+    #[test]
+    fn test_monitor_release_on_disconnect() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().connect(standard_pad(1));
+        for i in 0..2 {
+            monitor.source_mut().press(i, 1);
+            monitor.source_mut().set_axis(i, 0, -0.5);
+            monitor.source_mut().bump_timestamp(i);
+        }
+        drain(&mut monitor);
+
+        monitor.source_mut().disconnect(0);
+        assert_eq!(iter::from_fn(|| monitor.poll_mapped()).collect::<Vec<_>>(), vec![
+            MappedEvent::Axis(Axis::LeftStickX, 0.0),
+            MappedEvent::ButtonRelease(Button::East),
+            MappedEvent::ButtonValue(Button::East, 0.0),
+            MappedEvent::Stick(Stick::Left, 0.0, 0.0),
+        ]);
+
+        // also when the slot list shrinks
+        monitor.source_mut().resize(1);
+        assert_eq!(drain(&mut monitor), vec![
+            (1, EventData::Axis(0, 0.0)),
+            (1, EventData::Button(1, false)),
+            (1, EventData::ButtonValue(1, 0.0)),
+            (1, EventData::Disconnected),
+        ]);
+
+        monitor.set_release_on_disconnect(false);
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().press(0, 2);
+        drain(&mut monitor);
+        monitor.source_mut().disconnect(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Disconnected)]);
+    }
+
+    #[test]
+    fn test_monitor_focus() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().press(0, 0);
+        drain(&mut monitor);
+
+        // releases, then pauses
+        monitor.source_mut().set_focus(false);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Button(0, false)),
+            (0, EventData::ButtonValue(0, 0.0)),
+            (0, EventData::FocusLost),
+        ]);
+        monitor.source_mut().press(0, 1);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![]);
+
+        // held input is taken without presses, later changes are reported
+        monitor.source_mut().set_focus(true);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::FocusGained)]);
+        assert!(monitor.pad_state(0).unwrap().button_pressed(1));
+        monitor.source_mut().release(0, 1);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Button(1, false)),
+            (0, EventData::ButtonValue(1, 0.0)),
+        ]);
+
+        // focus lost and regained between polls, with a new pad meanwhile
+        monitor.source_mut().set_focus(false);
+        monitor.source_mut().set_focus(true);
+        monitor.source_mut().connect(standard_pad(1));
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Button(0, false)),
+            (0, EventData::ButtonValue(0, 0.0)),
+            (0, EventData::FocusLost),
+            (0, EventData::FocusGained),
+            (1, EventData::Connected),
+        ]);
+
+        // reconfigured, or replaced by a different pad, while unfocused
+        monitor.source_mut().set_focus(false);
+        drain(&mut monitor);
+        monitor.source_mut().reconfigure(0, GamepadMappingType::Standard, 6, 17);
+        monitor.source_mut().set_axis(0, 5, 0.5);
+        monitor.source_mut().set_focus(true);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Reconfigured),
+            (0, EventData::FocusGained),
+            (1, EventData::FocusGained),
+        ]);
+        assert_eq!(monitor.pad_description(0).unwrap().axis_count, 6);
+        assert_eq!(monitor.pad_state(0).unwrap().axis(5), 0.5);
+
+        monitor.source_mut().set_focus(false);
+        drain(&mut monitor);
+        monitor.source_mut().connect(GamepadDescription::new(0, "other pad".into(), GamepadMappingType::Standard, 4, 17));
+        monitor.source_mut().press(0, 2);
+        monitor.source_mut().set_focus(true);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Disconnected),
+            (0, EventData::Connected),
+            (0, EventData::Button(2, true)),
+            (0, EventData::ButtonValue(2, 1.0)),
+            (1, EventData::FocusGained),
+        ]);
+        assert_eq!(monitor.pad_description(0).unwrap().name, "other pad");
+    }
+
+    #[test]
+    fn test_monitor_rumble() {
+        let mut monitor = mock_monitor();
+        let mut desc = standard_pad(0);
+        desc.rumble.pulse = true;
+        monitor.source_mut().connect(desc);
+        monitor.source_mut().connect(standard_pad(1));
+
+        // not connected until polled
+        let effect = RumbleEffect::dual(200.0, 1.0, 0.5);
+        assert_eq!(monitor.rumble(0, effect), Err(RumbleError::NotConnected));
+        drain(&mut monitor);
+
+        assert_eq!(monitor.rumble(0, effect), Ok(()));
+        assert_eq!(monitor.stop_rumble(0), Ok(()));
+        assert_eq!(monitor.source().rumble_history(0), &[Some(effect), None]);
+
+        assert_eq!(monitor.rumble(1, effect), Err(RumbleError::Unsupported));
+        assert_eq!(monitor.stop_rumble(1), Err(RumbleError::Unsupported));
+        assert_eq!(monitor.rumble(2, effect), Err(RumbleError::NotConnected));
+    }
+
+    #[test]
+    fn test_monitor_connection_changes() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().set_connection_events(true);
+
+        // connected and disconnected between polls
+        monitor.source_mut().connect(standard_pad(1));
+        monitor.source_mut().disconnect(1);
+        assert_eq!(drain(&mut monitor), vec![(1, EventData::Connected), (1, EventData::Disconnected)]);
+
+        // seen by both the event and the snapshot, but only reported once
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Connected),
+            (0, EventData::Button(0, true)),
+            (0, EventData::ButtonValue(0, 1.0)),
+        ]);
+
+        // replaced between polls
+        monitor.source_mut().disconnect(0);
+        monitor.source_mut().connect(standard_pad(0));
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Button(0, false)),
+            (0, EventData::ButtonValue(0, 0.0)),
+            (0, EventData::Disconnected),
+            (0, EventData::Connected),
+        ]);
+
+        monitor.source_mut().disconnect(0);
+        monitor.source_mut().resize(0);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::Disconnected)]);
+
+        // changes while unfocused are reported once focus returns
+        monitor.source_mut().connect(standard_pad(0));
+        drain(&mut monitor);
+        monitor.source_mut().set_focus(false);
+        assert_eq!(drain(&mut monitor), vec![(0, EventData::FocusLost)]);
+        monitor.source_mut().connect(standard_pad(1));
+        monitor.source_mut().disconnect(1);
+        assert_eq!(drain(&mut monitor), vec![]);
+        monitor.source_mut().set_focus(true);
+        assert_eq!(drain(&mut monitor), vec![
+            (1, EventData::Connected),
+            (1, EventData::Disconnected),
+            (0, EventData::FocusGained),
+        ]);
+    }
+
+    #[test]
+    fn test_monitor_poll_into() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().press(0, 2);
+        monitor.source_mut().set_axis(0, 0, 0.5);
+        monitor.source_mut().bump_timestamp(0);
+
+        let mut events = vec![];
+        monitor.poll_into(&mut events);
+        let data: Vec<_> = events.iter().map(|event| event.data).collect();
+        assert_eq!(data, vec![
+            EventData::Connected,
+            EventData::Axis(0, 0.5),
+            EventData::Button(2, true),
+            EventData::ButtonValue(2, 1.0),
+        ]);
+
+        monitor.source_mut().release(0, 2);
+        monitor.source_mut().set_axis(0, 0, 0.0);
+        monitor.source_mut().bump_timestamp(0);
+        let mut mapped = vec![];
+        monitor.poll_mapped_into(&mut mapped);
+        assert_eq!(mapped, vec![
+            MappedEvent::Axis(Axis::LeftStickX, 0.0),
+            MappedEvent::ButtonRelease(Button::West),
+            MappedEvent::ButtonValue(Button::West, 0.0),
+            MappedEvent::Stick(Stick::Left, 0.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_monitor_reconfigure() {
+        let mut monitor = mock_monitor();
+        monitor.source_mut().connect(standard_pad(0));
+        monitor.source_mut().press(0, 0);
+        monitor.source_mut().press(0, 16);
+        monitor.source_mut().bump_timestamp(0);
+        drain(&mut monitor);
+
+        // the removed button is released, the new axis reported, and the held one kept
+        monitor.source_mut().reconfigure(0, GamepadMappingType::Standard, 6, 16);
+        monitor.source_mut().set_axis(0, 5, 1.0);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Button(16, false)),
+            (0, EventData::ButtonValue(16, 0.0)),
+            (0, EventData::Reconfigured),
+            (0, EventData::Axis(5, 1.0)),
+        ]);
+        let desc = monitor.pad_description(0).unwrap();
+        assert_eq!((desc.axis_count, desc.button_count), (6, 16));
+        assert_eq!(monitor.pad_state(0).unwrap().axis_count(), 6);
+
+        // a mapping change releases everything, then presses what is still held
+        monitor.source_mut().reconfigure(0, GamepadMappingType::NoMapping, 6, 16);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Axis(5, 0.0)),
+            (0, EventData::Button(0, false)),
+            (0, EventData::ButtonValue(0, 0.0)),
+            (0, EventData::Reconfigured),
+        ]);
+        monitor.source_mut().bump_timestamp(0);
+        assert_eq!(drain(&mut monitor), vec![
+            (0, EventData::Axis(5, 1.0)),
+            (0, EventData::Button(0, true)),
+            (0, EventData::ButtonValue(0, 1.0)),
+        ]);
+        assert_eq!(monitor.pad_description(0).unwrap().mapping, GamepadMappingType::NoMapping);
+    }
 }
@@ -0,0 +1,319 @@
+use ::event::{
+    Event,
+    EventData,
+    MappedEvent,
+    PlayerMappedEvent,
+};
+use ::gamepad::GamepadDescription;
+use ::mapping::Button;
+
+/// A change to the players, from [PlayerSlots::handle()](struct.PlayerSlots.html#method.handle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerEvent {
+    /// A gamepad pressed the join button, and took a free slot.
+    Joined {
+        /// The player's slot.
+        player: usize,
+        /// The gamepad's index.
+        pad: usize,
+    },
+    /// A player's gamepad disconnected. The slot stays reserved for it.
+    Left {
+        /// The player's slot.
+        player: usize,
+        /// The index of the gamepad that disconnected.
+        pad: usize,
+    },
+    /// A gamepad matching a disconnected player connected, and took back its slot.
+    Reconnected {
+        /// The player's slot.
+        player: usize,
+        /// The gamepad's index, which may differ from before.
+        pad: usize,
+    },
+}
+
+/// What identifies a gamepad across reconnections.
+#[derive(Debug, Clone, PartialEq)]
+struct DeviceKey {
+    name: String,
+    ids: Option<(u16, u16)>,
+}
+
+impl DeviceKey {
+    fn new(desc: &GamepadDescription) -> Self {
+        Self {
+            name: desc.name.clone(),
+            ids: desc.identity().map(|identity| (identity.vendor_id, identity.product_id)),
+        }
+    }
+
+    fn matches(&self, other: &DeviceKey) -> bool {
+        match (self.ids, other.ids) {
+            (Some(ids), Some(other_ids)) => ids == other_ids,
+            _ => self.name == other.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    /// The gamepad's index, or `None` while disconnected.
+    pad: Option<usize>,
+    key: DeviceKey,
+    /// When the gamepad connected, counted over all slots.
+    connection: u64,
+}
+
+/// Player slots, joined by pressing a button, which survive disconnections.
+///
+/// A gamepad joins by pressing the [join button](#method.set_join_button), taking the first free slot.
+/// When it disconnects, its slot is reserved until a gamepad with the same identity
+/// (vendor and product, or name) connects, even at a different index.
+/// If several reserved slots match, the one whose gamepad connected first is taken.
+///
+/// It is updated from raw events, like [InputState::handle()](struct.InputState.html#method.handle):
+///
+/// ```no_run
+/// use gamepad_web::*;
+///
+/// let mut monitor = Monitor::new();
+/// let mut players = PlayerSlots::new(4);
+///
+/// while let Some(event) = monitor.poll() {
+///     if let Some(change) = players.handle(&event) {
+///         // show "Player 2 joined", "Player 2 disconnected", ...
+///     }
+///     players.map_each(&event, |PlayerMappedEvent { player, event }| {
+///         // move player
+///     });
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PlayerSlots {
+    slots: Vec<Option<Slot>>,
+    join_button: Button,
+    connections: u64,
+}
+
+impl PlayerSlots {
+
+    /// Create `max_players` empty slots.
+    pub fn new(max_players: usize) -> Self {
+        Self {
+            slots: vec![None; max_players],
+            join_button: Button::Start,
+            connections: 0,
+        }
+    }
+
+    /// Set the button which joins a free slot. [Start](enum.Button.html#variant.Start) by default.
+    pub fn set_join_button(&mut self, button: Button) {
+        self.join_button = button;
+    }
+
+    /// The number of slots.
+    pub fn max_players(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Update the slots from a raw event.
+    pub fn handle(&mut self, event: &Event) -> Option<PlayerEvent> {
+        if event.gamepad.index < 0 {
+            return None;
+        }
+        let pad = event.gamepad.index as usize;
+
+        match event.data {
+            EventData::Connected => {
+                let key = DeviceKey::new(&event.gamepad);
+                let player = self.slots.iter()
+                    .enumerate()
+                    .filter_map(|(i, slot)| slot.as_ref().map(|slot| (i, slot)))
+                    .filter(|(_, slot)| slot.pad.is_none() && slot.key.matches(&key))
+                    .min_by_key(|(_, slot)| slot.connection)
+                    .map(|(i, _)| i)?;
+
+                self.connect(player, pad, key);
+                Some(PlayerEvent::Reconnected { player, pad })
+            },
+            EventData::Disconnected => {
+                let player = self.player(pad)?;
+                if let Some(ref mut slot) = self.slots[player] {
+                    slot.pad = None;
+                }
+                Some(PlayerEvent::Left { player, pad })
+            },
+            _ => {
+                // a hat switch or split axis may press the join button after other events
+                let mut join = false;
+                event.map_each(|mapped| join |= mapped == MappedEvent::ButtonPress(self.join_button));
+                if !join || self.player(pad).is_some() {
+                    return None;
+                }
+                let player = self.slots.iter().position(Option::is_none)?;
+
+                self.connect(player, pad, DeviceKey::new(&event.gamepad));
+                Some(PlayerEvent::Joined { player, pad })
+            },
+        }
+    }
+
+    fn connect(&mut self, player: usize, pad: usize, key: DeviceKey) {
+        self.slots[player] = Some(Slot {
+            pad: Some(pad),
+            key,
+            connection: self.connections,
+        });
+        self.connections += 1;
+    }
+
+    /// Map a raw input event, for the player whose gamepad emitted it.
+    ///
+    /// Returns `None` if the event is not mapped input, or its gamepad has not joined.
+    /// Like [Event::map()](struct.Event.html#method.map), this returns the first mapped event;
+    /// [map_each()](#method.map_each) passes on all of them.
+    pub fn map(&self, event: &Event) -> Option<PlayerMappedEvent> {
+        let mut first = None;
+        self.map_each(event, |mapped| { first.get_or_insert(mapped); });
+        first
+    }
+
+    /// Map a raw input event, for the player whose gamepad emitted it,
+    /// passing each mapped event to `f`, as a hat switch may press several buttons.
+    ///
+    /// Nothing is passed if the event is not mapped input, or its gamepad has not joined.
+    pub fn map_each<F: FnMut(PlayerMappedEvent)>(&self, event: &Event, mut f: F) {
+        if event.gamepad.index < 0 {
+            return;
+        }
+        if let Some(player) = self.player(event.gamepad.index as usize) {
+            event.map_each(|event| f(PlayerMappedEvent { player, event }));
+        }
+    }
+
+    /// The player using the gamepad at this index.
+    pub fn player(&self, pad: usize) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.as_ref().map_or(false, |slot| slot.pad == Some(pad)))
+    }
+
+    /// The index of this player's gamepad, or `None` if the slot is free or disconnected.
+    pub fn pad(&self, player: usize) -> Option<usize> {
+        self.slots.get(player)
+            .and_then(Option::as_ref)
+            .and_then(|slot| slot.pad)
+    }
+
+    /// Is this slot taken, even if its gamepad is disconnected?
+    pub fn is_taken(&self, player: usize) -> bool {
+        self.slots.get(player).map_or(false, Option::is_some)
+    }
+
+    /// Free this slot, so it may be joined by any gamepad.
+    pub fn remove(&mut self, player: usize) {
+        if let Some(slot) = self.slots.get_mut(player) {
+            *slot = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::GamepadMappingType;
+    use ::table::MappingTable;
+
+    use std::rc::Rc;
+
+    fn pad(index: i32, name: &str) -> Rc<GamepadDescription> {
+        Rc::new(GamepadDescription::new(index, name.into(), GamepadMappingType::Standard, 4, 17))
+    }
+
+    fn event(pad: &Rc<GamepadDescription>, data: EventData) -> Event {
+        Event::new(pad.clone(), data, 0.0, 0)
+    }
+
+    #[test]
+    fn test_players_join() {
+        let mut players = PlayerSlots::new(2);
+        let pads: Vec<_> = (0..3).map(|i| pad(i, "045e-028e-Xbox")).collect();
+
+        for pad in &pads {
+            assert_eq!(players.handle(&event(pad, EventData::Connected)), None);
+        }
+        assert_eq!(players.handle(&event(&pads[1], EventData::Button(0, true))), None);
+        assert_eq!(players.handle(&event(&pads[1], EventData::Button(9, true))), Some(PlayerEvent::Joined { player: 0, pad: 1 }));
+        assert_eq!(players.handle(&event(&pads[1], EventData::Button(9, true))), None);
+        assert_eq!(players.handle(&event(&pads[2], EventData::Button(9, true))), Some(PlayerEvent::Joined { player: 1, pad: 2 }));
+        // full
+        assert_eq!(players.handle(&event(&pads[0], EventData::Button(9, true))), None);
+
+        assert_eq!(players.player(1), Some(0));
+        assert_eq!(players.pad(1), Some(2));
+        assert_eq!(players.map(&event(&pads[2], EventData::Button(0, true))),
+            Some(PlayerMappedEvent { player: 1, event: MappedEvent::ButtonPress(Button::South) }));
+        assert_eq!(players.map(&event(&pads[0], EventData::Button(0, true))), None);
+
+        players.remove(0);
+        assert!(!players.is_taken(0));
+        assert_eq!(players.handle(&event(&pads[0], EventData::Button(9, true))), Some(PlayerEvent::Joined { player: 0, pad: 0 }));
+    }
+
+    #[test]
+    fn test_players_join_with_hat() {
+        let mut players = PlayerSlots::new(2);
+        players.set_join_button(Button::Right);
+        let mut table = MappingTable::new();
+        table.bind_hat(0);
+        let mut desc = GamepadDescription::new(0, "0079-0011-USB Gamepad".into(), GamepadMappingType::NoMapping, 1, 10);
+        desc.mapping_table = Some(Rc::new(table));
+        let pad = Rc::new(desc);
+
+        // Right is mapped after Up
+        let right = event(&pad, EventData::Axis(0, -3.0 / 7.0));
+        assert_eq!(players.handle(&right), Some(PlayerEvent::Joined { player: 0, pad: 0 }));
+
+        let mut mapped = vec![];
+        players.map_each(&right, |event| mapped.push(event.event));
+        assert_eq!(mapped, vec![
+            MappedEvent::ButtonRelease(Button::Up),
+            MappedEvent::ButtonValue(Button::Up, 0.0),
+            MappedEvent::ButtonPress(Button::Right),
+            MappedEvent::ButtonValue(Button::Right, 1.0),
+            MappedEvent::ButtonRelease(Button::Down),
+            MappedEvent::ButtonValue(Button::Down, 0.0),
+            MappedEvent::ButtonRelease(Button::Left),
+            MappedEvent::ButtonValue(Button::Left, 0.0),
+        ]);
+        assert_eq!(players.map(&right), Some(PlayerMappedEvent { player: 0, event: MappedEvent::ButtonRelease(Button::Up) }));
+    }
+
+    #[test]
+    fn test_players_reconnect() {
+        let mut players = PlayerSlots::new(4);
+        let xbox_a = pad(0, "045e-028e-Xbox");
+        let xbox_b = pad(1, "045e-028e-Xbox");
+        let dualshock = pad(2, "054c-05c4-Wireless Controller");
+        for (pad, player) in [&dualshock, &xbox_a, &xbox_b].iter().zip(0..) {
+            assert_eq!(players.handle(&event(pad, EventData::Button(9, true))),
+                Some(PlayerEvent::Joined { player, pad: pad.index as usize }));
+        }
+
+        // both Xbox pads drop, then come back in the other order, at new indices
+        assert_eq!(players.handle(&event(&xbox_b, EventData::Disconnected)), Some(PlayerEvent::Left { player: 2, pad: 1 }));
+        assert_eq!(players.handle(&event(&xbox_a, EventData::Disconnected)), Some(PlayerEvent::Left { player: 1, pad: 0 }));
+        assert!(players.is_taken(1));
+        assert_eq!(players.pad(1), None);
+
+        // a different device doesn't take a reserved slot
+        let generic = pad(0, "0079-0011-USB Gamepad");
+        assert_eq!(players.handle(&event(&generic, EventData::Connected)), None);
+
+        // the first-connected pad's slot is taken first
+        let xbox_c = pad(3, "045e-028e-Xbox");
+        let xbox_d = pad(1, "045e-028e-Xbox");
+        assert_eq!(players.handle(&event(&xbox_c, EventData::Connected)), Some(PlayerEvent::Reconnected { player: 1, pad: 3 }));
+        assert_eq!(players.handle(&event(&xbox_d, EventData::Connected)), Some(PlayerEvent::Reconnected { player: 2, pad: 1 }));
+        assert_eq!(players.player(3), Some(1));
+    }
+}
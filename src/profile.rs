@@ -0,0 +1,366 @@
+use ::action::{
+    ActionMap,
+    Binding,
+};
+use ::deadzone::DeadZones;
+use ::gamepad::GamepadDescription;
+use ::sdl::SdlMapping;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::error::Error;
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+/// Prefix of the keys profiles are stored under.
+const STORAGE_PREFIX: &str = "gamepad-web/profile/";
+
+/// The kind of gamepad a [Profile](struct.Profile.html) applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProfileKey {
+    /// USB vendor and product IDs, matching the same model in any browser.
+    Ids {
+        /// The USB vendor ID.
+        vendor_id: u16,
+        /// The USB product ID.
+        product_id: u16,
+    },
+    /// The gamepad's [name](struct.GamepadDescription.html#structfield.name), if it has no IDs.
+    Name(String),
+}
+
+impl ProfileKey {
+
+    /// The key for a gamepad, by its [identity](struct.GamepadDescription.html#method.identity) if known.
+    pub fn for_gamepad(desc: &GamepadDescription) -> Self {
+        match desc.identity() {
+            Some(identity) => ProfileKey::Ids {
+                vendor_id: identity.vendor_id,
+                product_id: identity.product_id,
+            },
+            None => ProfileKey::Name(desc.name.clone()),
+        }
+    }
+
+    /// The key a profile for this device is stored under, such as `gamepad-web/profile/045e-028e`.
+    pub fn storage_key(&self) -> String {
+        match *self {
+            ProfileKey::Ids { vendor_id, product_id } => format!("{}{:04x}-{:04x}", STORAGE_PREFIX, vendor_id, product_id),
+            ProfileKey::Name(ref name) => format!("{}name/{}", STORAGE_PREFIX, name),
+        }
+    }
+}
+
+/// An error saving or loading a [Profile](struct.Profile.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    /// The profile could not be written as JSON, or stored JSON is not a valid profile. (error message)
+    Format(String),
+    /// The storage could not save the profile, as when it is full. (reason)
+    Storage(String),
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ProfileError::Format(ref message) => write!(f, "invalid profile: {}", message),
+            ProfileError::Storage(ref reason) => write!(f, "could not store profile: {}", reason),
+        }
+    }
+}
+
+impl Error for ProfileError {}
+
+impl From<serde_json::Error> for ProfileError {
+    fn from(err: serde_json::Error) -> Self {
+        ProfileError::Format(err.to_string())
+    }
+}
+
+/// Somewhere to keep profiles, as strings by key.
+///
+/// [LocalStorage](struct.LocalStorage.html) keeps them in the browser,
+/// and [MemoryStorage](struct.MemoryStorage.html) only until it is dropped.
+pub trait ProfileStorage {
+
+    /// The value stored under `key`, if any.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Store `value` under `key`, replacing any earlier value.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ProfileError>;
+
+    /// Remove the value stored under `key`, if any.
+    fn remove(&mut self, key: &str);
+}
+
+/// Profiles kept in memory, for tests and native builds.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemoryStorage {
+    values: HashMap<String, String>,
+}
+
+impl MemoryStorage {
+
+    /// Create an empty storage.
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+}
+
+impl ProfileStorage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ProfileError> {
+        self.values.insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+}
+
+/// The browser's [localStorage](https://developer.mozilla.org/en-US/docs/Web/API/Window/localStorage),
+/// which keeps profiles between visits.
+#[cfg(any(feature = "stdweb", feature = "web_sys"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LocalStorage;
+
+#[cfg(feature = "stdweb")]
+impl ProfileStorage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        ::stdweb::web::window().local_storage().get(key)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ProfileError> {
+        ::stdweb::web::window().local_storage().insert(key, value)
+            .map_err(|_| ProfileError::Storage("localStorage is full".into()))
+    }
+
+    fn remove(&mut self, key: &str) {
+        ::stdweb::web::window().local_storage().remove(key);
+    }
+}
+
+#[cfg(all(feature = "web_sys", not(feature = "stdweb")))]
+fn local_storage() -> Option<::web_sys::Storage> {
+    ::web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(all(feature = "web_sys", not(feature = "stdweb")))]
+impl ProfileStorage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        local_storage()?.get_item(key).ok().flatten()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ProfileError> {
+        let storage = local_storage().ok_or_else(|| ProfileError::Storage("localStorage is unavailable".into()))?;
+        storage.set_item(key, value)
+            .map_err(|_| ProfileError::Storage("localStorage is full".into()))
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
+
+/// A player's settings for one kind of gamepad: action bindings,
+/// and a custom mapping if its browser mapping is unknown.
+///
+/// Profiles are stored as JSON, which may be edited by hand:
+///
+/// ```json
+/// {
+///   "device": { "Ids": { "vendor_id": 1118, "product_id": 654 } },
+///   "bindings": {
+///     "Jump": [ { "Button": "South" } ],
+///     "MoveX": [ { "Axis": "LeftStickX" }, { "ButtonAxis": [ "Left", "Right" ] } ]
+///   }
+/// }
+/// ```
+///
+/// Every field but `device` may be left out.
+/// Actions are written as JSON object keys, so they must serialize as strings,
+/// like the variants of a fieldless enum.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "A: ::serde::Deserialize<'de> + Ord"))]
+pub struct Profile<A: Ord> {
+    /// The kind of gamepad this profile applies to.
+    pub device: ProfileKey,
+    /// The bindings of each action.
+    #[serde(default)]
+    pub bindings: BTreeMap<A, Vec<Binding>>,
+    /// A mapping for the gamepad, to add to the monitor's
+    /// [SdlDatabase](struct.SdlDatabase.html#method.add).
+    #[serde(default)]
+    pub mapping: Option<SdlMapping>,
+    /// Dead zones for the gamepad, to set with
+    /// [Monitor::set_pad_dead_zones()](struct.Monitor.html#method.set_pad_dead_zones).
+    #[serde(default)]
+    pub dead_zones: Option<DeadZones>,
+}
+
+impl<A: Copy + Ord> Profile<A> {
+
+    /// Create an empty profile for this kind of gamepad.
+    pub fn new(device: ProfileKey) -> Self {
+        Self {
+            device,
+            bindings: BTreeMap::new(),
+            mapping: None,
+            dead_zones: None,
+        }
+    }
+
+    /// Replace the profile's bindings with those of an action map.
+    pub fn set_bindings(&mut self, actions: &ActionMap<A>) {
+        self.bindings.clear();
+        for &(action, binding) in actions.all_bindings() {
+            self.bindings.entry(action).or_default().push(binding);
+        }
+    }
+
+    /// Replace the bindings of an action map with the profile's.
+    pub fn apply_bindings(&self, actions: &mut ActionMap<A>) {
+        actions.set_bindings(self.bindings.iter()
+            .flat_map(|(&action, bindings)| bindings.iter().map(move |&binding| (action, binding))));
+    }
+}
+
+impl<A: Copy + Ord + Serialize + DeserializeOwned> Profile<A> {
+
+    /// Write the profile as indented JSON.
+    pub fn to_json(&self) -> Result<String, ProfileError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Read a profile from JSON.
+    pub fn from_json(json: &str) -> Result<Self, ProfileError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Store the profile, under its [device's key](enum.ProfileKey.html#method.storage_key).
+    pub fn save<S: ProfileStorage + ?Sized>(&self, storage: &mut S) -> Result<(), ProfileError> {
+        storage.set(&self.device.storage_key(), &self.to_json()?)
+    }
+
+    /// Load the profile for a kind of gamepad, or `None` if none is stored.
+    pub fn load<S: ProfileStorage + ?Sized>(storage: &S, device: &ProfileKey) -> Result<Option<Self>, ProfileError> {
+        storage.get(&device.storage_key())
+            .map(|json| Self::from_json(&json))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::deadzone::{
+        DeadZone,
+        DeadZoneShape,
+    };
+    use ::event::{
+        EventData,
+        MappedEvent,
+    };
+    use ::gamepad::GamepadMappingType;
+    use ::mapping::{
+        Axis,
+        Button,
+    };
+    use ::sdl::AxisHalf;
+
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    enum Action {
+        Jump,
+        MoveX,
+    }
+
+    const XBOX_360: &str = "030000005e0400008e02000010010000,Xbox 360 Controller,a:b0,dpup:h0.1,lefttrigger:a2,-leftx:-a0~,platform:Linux,";
+
+    fn profile() -> Profile<Action> {
+        let mut actions = ActionMap::new();
+        actions.bind(Action::Jump, Binding::Button(Button::South));
+        actions.bind(Action::MoveX, Binding::HalfAxis(Axis::LeftStickX, AxisHalf::Positive));
+        actions.bind(Action::MoveX, Binding::ButtonAxis(Button::Left, Button::Right));
+
+        let mut profile = Profile::new(ProfileKey::Ids { vendor_id: 0x045e, product_id: 0x028e });
+        profile.set_bindings(&actions);
+        profile.mapping = Some(XBOX_360.parse().unwrap());
+        profile.dead_zones = Some(DeadZones::both(DeadZone::new(DeadZoneShape::Radial, 0.1 + 0.2, 0.95)));
+        profile
+    }
+
+    #[test]
+    fn test_profile_round_trip() {
+        let profile = profile();
+        let json = profile.to_json().unwrap();
+        assert_eq!(Profile::from_json(&json), Ok(profile.clone()));
+
+        let mut actions = ActionMap::new();
+        profile.apply_bindings(&mut actions);
+        assert_eq!(actions.bindings(Action::MoveX).collect::<Vec<_>>(), vec![
+            Binding::HalfAxis(Axis::LeftStickX, AxisHalf::Positive),
+            Binding::ButtonAxis(Button::Left, Button::Right),
+        ]);
+
+        // hand-written, with optional fields left out
+        let json = r#"{ "device": { "Name": "pad" }, "bindings": { "Jump": [ { "Button": "East" } ] } }"#;
+        let profile: Profile<Action> = Profile::from_json(json).unwrap();
+        assert_eq!(profile.bindings[&Action::Jump], vec![Binding::Button(Button::East)]);
+        assert_eq!(profile.mapping, None);
+
+        match Profile::<Action>::from_json(r#"{ "bindings": {} }"#) {
+            Err(ProfileError::Format(_)) => (),
+            other => panic!("expected a format error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_profile_storage() {
+        let mut storage = MemoryStorage::new();
+        let profile = profile();
+        let key = ProfileKey::for_gamepad(&GamepadDescription::new(0, "045e-028e-Xbox 360 pad".into(), GamepadMappingType::Standard, 4, 17));
+        assert_eq!(key, profile.device);
+        assert_eq!(key.storage_key(), "gamepad-web/profile/045e-028e");
+        assert_eq!(Profile::<Action>::load(&storage, &key), Ok(None));
+
+        profile.save(&mut storage).unwrap();
+        assert_eq!(Profile::load(&storage, &key), Ok(Some(profile)));
+
+        storage.remove(&key.storage_key());
+        assert_eq!(Profile::<Action>::load(&storage, &key), Ok(None));
+    }
+
+    #[test]
+    fn test_serde_types() {
+        let mut desc = GamepadDescription::new(1, "Pad".into(), GamepadMappingType::NoMapping, 2, 3);
+        desc.sdl_mapping = Some(Rc::new(XBOX_360.parse().unwrap()));
+        let json = serde_json::to_string(&desc).unwrap();
+        assert_eq!(serde_json::from_str::<GamepadDescription>(&json).unwrap(), desc);
+
+        let data = [EventData::Reconfigured, EventData::Axis(1, -0.1), EventData::ButtonValue(0, 1.0 / 3.0)];
+        let json = serde_json::to_string(&data).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<EventData>>(&json).unwrap(), data);
+
+        let event = MappedEvent::ButtonPress(Button::Home);
+        assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"ButtonPress":"Home"}"#);
+    }
+}
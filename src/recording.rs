@@ -0,0 +1,951 @@
+use ::gamepad::{
+    GamepadDescription,
+    GamepadMappingType,
+    GamepadState,
+    GamepadStateChange,
+};
+use ::rumble::{
+    RumbleCapabilities,
+    RumbleEffect,
+    RumbleError,
+};
+use ::source::{
+    ConnectionChange,
+    FocusState,
+    GamepadSource,
+};
+
+use std::error::Error;
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+use std::io::{
+    self,
+    BufRead,
+    Write,
+};
+use std::mem;
+use std::str::FromStr;
+
+/// The first line of a recording file.
+const HEADER: &str = "gamepad-web recording";
+
+/// The version of the recording format written by this crate.
+const VERSION: u32 = 3;
+
+type Snapshot = Vec<Option<(GamepadDescription, GamepadState)>>;
+
+/// A raw event, as stored in a recording.
+#[derive(Debug, Clone, PartialEq)]
+enum Record {
+    /// The number of gamepad slots changed.
+    Slots(usize),
+    /// A gamepad connected, with all axes at 0.0 and buttons released.
+    Connected(GamepadDescription),
+    /// The gamepad at this index disconnected.
+    Disconnected(usize),
+    /// A gamepad's timestamp changed.
+    Timestamp(usize, f64),
+    /// A gamepad's input changed.
+    Change(usize, GamepadStateChange),
+}
+
+/// The changes seen by one snapshot.
+#[derive(Debug, Clone, PartialEq)]
+struct Frame {
+    time: f64,
+    /// The source's focus, checked before the snapshot.
+    focus: FocusState,
+    /// Connection changes reported by the source, before the snapshot.
+    connections: Vec<ConnectionChange>,
+    records: Vec<Record>,
+}
+
+/// Apply a record to a snapshot.
+fn apply(pads: &mut Snapshot, record: &Record) {
+    match *record {
+        Record::Slots(len) =>
+            pads.resize(len, None),
+        Record::Connected(ref desc) => {
+            let index = desc.index as usize;
+            if index >= pads.len() {
+                pads.resize(index + 1, None);
+            }
+            pads[index] = Some((desc.clone(), desc.into()));
+        },
+        Record::Disconnected(index) =>
+            pads[index] = None,
+        Record::Timestamp(index, timestamp) =>
+            if let Some((_, ref mut state)) = pads[index] {
+                state.set_timestamp(timestamp);
+            },
+        Record::Change(index, change) =>
+            if let Some((_, ref mut state)) = pads[index] {
+                match change {
+                    GamepadStateChange::Axis(i, value) => state.set_axis(i, value),
+                    GamepadStateChange::Button(i, pressed) => state.set_button_pressed(i, pressed),
+                    GamepadStateChange::ButtonValue(i, value) => state.set_button_value(i, value),
+                }
+            },
+    }
+}
+
+/// Check that a record only refers to slots, axes, and buttons which exist,
+/// given the (axis count, button count) of each slot, and apply it to them.
+fn check(layouts: &mut Vec<Option<(usize, usize)>>, record: &Record) -> bool {
+    match *record {
+        Record::Slots(len) =>
+            layouts.resize(len, None),
+        Record::Connected(ref desc) => {
+            let index = desc.index as usize;
+            if index >= layouts.len() {
+                layouts.resize(index + 1, None);
+            }
+            layouts[index] = Some((desc.axis_count, desc.button_count));
+        },
+        Record::Disconnected(index) =>
+            match layouts.get_mut(index) {
+                Some(layout) => *layout = None,
+                None => return false,
+            },
+        Record::Timestamp(index, _) =>
+            return index < layouts.len(),
+        Record::Change(index, change) => {
+            // changes to empty slots are ignored
+            let (axis_count, button_count) = match layouts.get(index) {
+                Some(&Some(layout)) => layout,
+                Some(&None) => return true,
+                None => return false,
+            };
+            return match change {
+                GamepadStateChange::Axis(i, _) => i < axis_count,
+                GamepadStateChange::Button(i, _) | GamepadStateChange::ButtonValue(i, _) => i < button_count,
+            };
+        },
+    }
+    true
+}
+
+/// Find the records that turn `previous` into `next`.
+fn diff(previous: &Snapshot, next: &Snapshot) -> Vec<Record> {
+    let mut records = vec![];
+    if previous.len() != next.len() {
+        records.push(Record::Slots(next.len()));
+    }
+
+    for (i, pad) in next.iter().enumerate() {
+        let old = previous.get(i).and_then(Option::as_ref);
+        match (old, pad) {
+            (Some(_), None) =>
+                records.push(Record::Disconnected(i)),
+            (old, Some((desc, state))) => {
+                let baseline;
+                let old_state = match old {
+                    Some((old_desc, old_state)) if old_desc == desc => old_state,
+                    _ => {
+                        records.push(Record::Connected(desc.clone()));
+                        baseline = desc.into();
+                        &baseline
+                    },
+                };
+
+                if old_state.timestamp().to_bits() != state.timestamp().to_bits() {
+                    records.push(Record::Timestamp(i, state.timestamp()));
+                }
+                records.extend(state.changes_since(old_state).map(|change| Record::Change(i, change)));
+            },
+            (None, None) => (),
+        }
+    }
+    records
+}
+
+/// An error reading a [Recording](struct.Recording.html).
+#[derive(Debug)]
+pub enum RecordingError {
+    /// The recording could not be read.
+    Io(io::Error),
+    /// The file is not a recording, or was written by a newer version of this crate.
+    UnsupportedVersion(String),
+    /// A line is malformed, or refers to a gamepad, axis, or button which does not exist.
+    /// (line number, counted from 1, and the line)
+    InvalidLine(usize, String),
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            RecordingError::Io(ref err) => write!(f, "error reading recording: {}", err),
+            RecordingError::UnsupportedVersion(ref header) => write!(f, "unsupported recording header \"{}\"", header),
+            RecordingError::InvalidLine(number, ref line) => write!(f, "invalid recording line {}: \"{}\"", number, line),
+        }
+    }
+}
+
+impl Error for RecordingError {}
+
+impl From<io::Error> for RecordingError {
+    fn from(err: io::Error) -> Self {
+        RecordingError::Io(err)
+    }
+}
+
+/// The fields of a gamepad description, but its name, as written to a recording.
+struct Fields<'a>(&'a GamepadDescription);
+
+impl<'a> Display for Fields<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let desc = self.0;
+        let mapping = match desc.mapping {
+            GamepadMappingType::Standard => "standard",
+            GamepadMappingType::NoMapping => "none",
+        };
+        let rumble = desc.rumble.dual_rumble as u8
+            | (desc.rumble.trigger_rumble as u8) << 1
+            | (desc.rumble.pulse as u8) << 2;
+        write!(f, "{} {} {} {} {}", desc.index, mapping, desc.axis_count, desc.button_count, rumble)
+    }
+}
+
+/// A gamepad name, escaped to fit on one line of a recording.
+struct Name<'a>(&'a str);
+
+impl<'a> Display for Name<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The state of a gamepad as it connected, as written to a recording.
+struct State<'a>(&'a GamepadState);
+
+impl<'a> Display for State<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let state = self.0;
+        write!(f, "{}", state.timestamp())?;
+        for i in 0..state.axis_count() {
+            write!(f, " {}", state.axis(i))?;
+        }
+        for i in 0..state.button_count() {
+            write!(f, " {} {}", state.button_pressed(i) as u8, state.button_value(i))?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a name written by `Name`.
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(unescaped)
+}
+
+/// A recorded input session, made by a [Recorder](struct.Recorder.html)
+/// and played back by a [ReplaySource](struct.ReplaySource.html).
+///
+/// Recordings are saved as versioned, line-based text:
+/// a header, then a `frame` line per snapshot, each followed by the raw events seen by that snapshot.
+/// Version 2 added the page focus, on a `focus` line after the `frame` line.
+/// Version 3 added gamepads' rumble capabilities, escapes line breaks and backslashes in their names,
+/// and added the source's connection changes, on `connection` lines.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Recording {
+    frames: Vec<Frame>,
+}
+
+impl Recording {
+
+    /// Create an empty recording.
+    pub fn new() -> Self {
+        Self {
+            frames: vec![],
+        }
+    }
+
+    /// The number of recorded snapshots.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Are there no recorded snapshots?
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The time of the first snapshot, or 0.0 if empty.
+    pub fn start_time(&self) -> f64 {
+        self.frames.first().map_or(0.0, |frame| frame.time)
+    }
+
+    /// The time of the last snapshot, or 0.0 if empty.
+    pub fn end_time(&self) -> f64 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+
+    /// Save the recording.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{} {}", HEADER, VERSION)?;
+        for frame in &self.frames {
+            writeln!(writer, "frame {}", frame.time)?;
+            match frame.focus {
+                FocusState::Focused => (),
+                FocusState::Unfocused => writeln!(writer, "focus unfocused")?,
+                FocusState::Refocused => writeln!(writer, "focus refocused")?,
+            }
+            for change in &frame.connections {
+                match *change {
+                    ConnectionChange::Connected(ref desc, ref state) =>
+                        writeln!(writer, "connection connect {} {} {}", Fields(desc), State(state), Name(&desc.name))?,
+                    ConnectionChange::Disconnected(index) =>
+                        writeln!(writer, "connection disconnect {}", index)?,
+                }
+            }
+            for record in &frame.records {
+                match *record {
+                    Record::Slots(len) =>
+                        writeln!(writer, "slots {}", len)?,
+                    Record::Connected(ref desc) =>
+                        writeln!(writer, "connect {} {}", Fields(desc), Name(&desc.name))?,
+                    Record::Disconnected(index) =>
+                        writeln!(writer, "disconnect {}", index)?,
+                    Record::Timestamp(index, timestamp) =>
+                        writeln!(writer, "timestamp {} {}", index, timestamp)?,
+                    Record::Change(index, GamepadStateChange::Axis(i, value)) =>
+                        writeln!(writer, "axis {} {} {}", index, i, value)?,
+                    Record::Change(index, GamepadStateChange::Button(i, pressed)) =>
+                        writeln!(writer, "button {} {} {}", index, i, pressed as u8)?,
+                    Record::Change(index, GamepadStateChange::ButtonValue(i, value)) =>
+                        writeln!(writer, "value {} {} {}", index, i, value)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Load a recording saved by [write_to()](#method.write_to).
+    pub fn read_from<R: BufRead>(reader: R) -> Result<Self, RecordingError> {
+        let mut lines = reader.lines();
+
+        let header = lines.next().unwrap_or_else(|| Ok(String::new()))?;
+        let version = header.trim().strip_prefix(HEADER)
+            .and_then(|version| version.trim().parse::<u32>().ok());
+        let version = match version {
+            Some(version) if (1..=VERSION).contains(&version) => version,
+            _ => return Err(RecordingError::UnsupportedVersion(header)),
+        };
+
+        let mut frames: Vec<Frame> = vec![];
+        let mut layouts = vec![];
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let invalid = || RecordingError::InvalidLine(i + 2, line.clone());
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // names run to the end of the line, so it is not trimmed there
+            let mut fields = line.trim_start().splitn(2, ' ');
+            let kind = fields.next().unwrap_or("");
+            let rest = fields.next().unwrap_or("");
+            if kind == "frame" {
+                frames.push(Frame {
+                    time: rest.trim().parse().map_err(|_| invalid())?,
+                    focus: FocusState::Focused,
+                    connections: vec![],
+                    records: vec![],
+                });
+                continue;
+            }
+            if kind == "connection" && version >= 3 {
+                let change = parse_connection(rest).ok_or_else(invalid)?;
+                frames.last_mut().ok_or_else(invalid)?.connections.push(change);
+                continue;
+            }
+            if kind == "focus" {
+                let frame = frames.last_mut().ok_or_else(invalid)?;
+                frame.focus = match rest.trim() {
+                    "unfocused" => FocusState::Unfocused,
+                    "refocused" => FocusState::Refocused,
+                    _ => return Err(invalid()),
+                };
+                continue;
+            }
+
+            let record = parse_record(kind, rest, version).ok_or_else(invalid)?;
+            if !check(&mut layouts, &record) {
+                return Err(invalid());
+            }
+            frames.last_mut().ok_or_else(invalid)?.records.push(record);
+        }
+
+        Ok(Self {
+            frames,
+        })
+    }
+}
+
+/// Take the next space-separated field from `rest`.
+fn next_field<'a>(rest: &mut &'a str) -> Option<&'a str> {
+    let mut fields = rest.splitn(2, ' ');
+    let field = fields.next().filter(|field| !field.is_empty())?;
+    *rest = fields.next().unwrap_or("");
+    Some(field)
+}
+
+/// Parse the next field of `rest`.
+fn parse_field<T: FromStr>(rest: &mut &str) -> Option<T> {
+    next_field(rest)?.parse().ok()
+}
+
+/// Read the description's fields written by `Fields`, leaving the name in `rest`.
+fn parse_fields(rest: &mut &str, version: u32) -> Option<GamepadDescription> {
+    let index = parse_field(rest)?;
+    let mapping = match next_field(rest)? {
+        "standard" => GamepadMappingType::Standard,
+        "none" => GamepadMappingType::NoMapping,
+        _ => return None,
+    };
+    let axis_count = parse_field(rest)?;
+    let button_count = parse_field(rest)?;
+    let rumble: u8 = if version >= 3 { parse_field(rest)? } else { 0 };
+    if index < 0 || rumble > 7 {
+        return None;
+    }
+
+    let mut desc = GamepadDescription::new(index, String::new(), mapping, axis_count, button_count);
+    desc.rumble = RumbleCapabilities {
+        dual_rumble: rumble & 1 != 0,
+        trigger_rumble: rumble & 2 != 0,
+        pulse: rumble & 4 != 0,
+    };
+    Some(desc)
+}
+
+/// Read a name, the rest of a line.
+fn parse_name(rest: &str, version: u32) -> Option<String> {
+    if version >= 3 { unescape(rest) } else { Some(rest.trim_end().into()) }
+}
+
+/// Read a connection change written by [Recording::write_to()](struct.Recording.html#method.write_to).
+fn parse_connection(rest: &str) -> Option<ConnectionChange> {
+    let mut rest = rest;
+    match next_field(&mut rest)? {
+        "connect" => {
+            let mut desc = parse_fields(&mut rest, VERSION)?;
+            let timestamp = parse_field(&mut rest)?;
+            let axes = (0..desc.axis_count)
+                .map(|_| parse_field(&mut rest))
+                .collect::<Option<_>>()?;
+            let buttons = (0..desc.button_count)
+                .map(|_| {
+                    let pressed = match next_field(&mut rest)? {
+                        "0" => false,
+                        "1" => true,
+                        _ => return None,
+                    };
+                    Some((pressed, parse_field(&mut rest)?))
+                })
+                .collect::<Option<_>>()?;
+            desc.name = parse_name(rest, VERSION)?;
+            Some(ConnectionChange::Connected(desc, GamepadState::new(timestamp, axes, buttons)))
+        },
+        "disconnect" => match rest.trim().parse() {
+            Ok(index) => Some(ConnectionChange::Disconnected(index)),
+            Err(_) => None,
+        },
+        _ => None,
+    }
+}
+
+fn parse_record(kind: &str, rest: &str, version: u32) -> Option<Record> {
+    fn field<T: FromStr>(fields: &mut dyn Iterator<Item=&str>) -> Option<T> {
+        fields.next()?.parse().ok()
+    }
+
+    if kind == "connect" {
+        let mut rest = rest;
+        let mut desc = parse_fields(&mut rest, version)?;
+        desc.name = parse_name(rest, version)?;
+        return Some(Record::Connected(desc));
+    }
+
+    let mut fields = rest.split_whitespace();
+    let record = match kind {
+        "slots" => Record::Slots(field(&mut fields)?),
+        "disconnect" => Record::Disconnected(field(&mut fields)?),
+        "timestamp" => Record::Timestamp(field(&mut fields)?, field(&mut fields)?),
+        "axis" => Record::Change(field(&mut fields)?, GamepadStateChange::Axis(field(&mut fields)?, field(&mut fields)?)),
+        "button" => {
+            let index = field(&mut fields)?;
+            let button = field(&mut fields)?;
+            let pressed = match fields.next()? {
+                "0" => false,
+                "1" => true,
+                _ => return None,
+            };
+            Record::Change(index, GamepadStateChange::Button(button, pressed))
+        },
+        "value" => Record::Change(field(&mut fields)?, GamepadStateChange::ButtonValue(field(&mut fields)?, field(&mut fields)?)),
+        _ => return None,
+    };
+    match fields.next() {
+        None => Some(record),
+        Some(_) => None,
+    }
+}
+
+/// A gamepad source which records every snapshot of another source.
+///
+/// [Connection changes](trait.GamepadSource.html#method.connection_changes) are passed through,
+/// and recorded with the following snapshot.
+///
+/// Use it in place of the source you want to record, and set the time before polling each frame:
+///
+/// ```no_run
+/// use gamepad_web::*;
+///
+/// let mut monitor = Monitor::with_source(Recorder::new(StdwebSource::new()));
+///
+/// # let now = 0.0;
+/// // each frame
+/// monitor.source_mut().set_time(now);
+/// while let Some(event) = monitor.poll() {
+///     // ...
+/// }
+///
+/// // later
+/// let mut file = Vec::new();
+/// monitor.source().recording().write_to(&mut file).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Recorder<S: GamepadSource> {
+    source: S,
+    time: f64,
+    focus: FocusState,
+    /// Connection changes reported since the last snapshot.
+    connections: Vec<ConnectionChange>,
+    previous: Snapshot,
+    recording: Recording,
+}
+
+impl<S: GamepadSource> Recorder<S> {
+
+    /// Start recording `source`.
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            time: 0.0,
+            focus: FocusState::Focused,
+            connections: vec![],
+            previous: vec![],
+            recording: Recording::new(),
+        }
+    }
+
+    /// Set the time (in milliseconds) recorded for the following snapshots.
+    ///
+    /// During replay, snapshots are played back at these times.
+    pub fn set_time(&mut self, now: f64) {
+        self.time = now;
+    }
+
+    /// Get the recorded source.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Get the recorded source, to change its configuration.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Get the recording so far.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Stop recording, returning the source and the recording.
+    pub fn finish(self) -> (S, Recording) {
+        (self.source, self.recording)
+    }
+}
+
+impl<S: GamepadSource> GamepadSource for Recorder<S> {
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>> {
+        let snapshot = self.source.snapshot();
+        self.recording.frames.push(Frame {
+            time: self.time,
+            focus: self.focus,
+            connections: mem::take(&mut self.connections),
+            records: diff(&self.previous, &snapshot),
+        });
+        self.focus = FocusState::Focused;
+        self.previous = snapshot.clone();
+        snapshot
+    }
+
+    fn focus(&mut self) -> FocusState {
+        self.focus = self.source.focus();
+        self.focus
+    }
+
+    fn connection_changes(&mut self) -> Vec<ConnectionChange> {
+        let changes = self.source.connection_changes();
+        self.connections.extend(changes.iter().cloned());
+        changes
+    }
+
+    fn rumble(&mut self, index: usize, effect: &RumbleEffect) -> Result<(), RumbleError> {
+        self.source.rumble(index, effect)
+    }
+
+    fn stop_rumble(&mut self, index: usize) -> Result<(), RumbleError> {
+        self.source.stop_rumble(index)
+    }
+}
+
+/// A gamepad source which plays back a [Recording](struct.Recording.html).
+///
+/// Each snapshot plays back one recorded snapshot, once its time is reached.
+/// Recorded snapshots are never merged, so a monitor polled the same way as during recording
+/// gives the same events, even if it falls behind.
+///
+/// Play back in real time with [set_time()](#method.set_time),
+/// or one frame at a time with [step()](#method.step).
+#[derive(Debug, Clone)]
+pub struct ReplaySource {
+    recording: Recording,
+    next: usize,
+    /// The number of frames whose connection changes were played back.
+    reported: usize,
+    time: f64,
+    focused: bool,
+    pads: Snapshot,
+}
+
+impl ReplaySource {
+
+    /// Start playback, before the first recorded snapshot.
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            next: 0,
+            reported: 0,
+            time: f64::NEG_INFINITY,
+            focused: true,
+            pads: vec![],
+        }
+    }
+
+    /// Play back snapshots recorded up to time `now`, in the recording's clock.
+    ///
+    /// For real time playback, add the elapsed time to [start_time()](struct.Recording.html#method.start_time).
+    pub fn set_time(&mut self, now: f64) {
+        self.time = now;
+    }
+
+    /// Play back the snapshots of the next recorded frame (those with the next recorded time).
+    ///
+    /// Returns `false` if the recording is finished.
+    pub fn step(&mut self) -> bool {
+        let next_time = self.recording.frames[self.next..].iter()
+            .map(|frame| frame.time)
+            .find(|&time| time > self.time);
+        if let Some(time) = next_time {
+            self.time = time;
+        }
+        !self.is_finished()
+    }
+
+    /// Have all snapshots been played back?
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.frames.len()
+    }
+
+    /// Get the recording.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// The next frame, if its time is reached.
+    fn due_frame(&self) -> Option<&Frame> {
+        self.recording.frames.get(self.next).filter(|frame| frame.time <= self.time)
+    }
+}
+
+impl GamepadSource for ReplaySource {
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>> {
+        if let Some(frame) = self.recording.frames.get(self.next).filter(|frame| frame.time <= self.time) {
+            for record in &frame.records {
+                apply(&mut self.pads, record);
+            }
+            self.focused = frame.focus != FocusState::Unfocused;
+            self.next += 1;
+        }
+        self.pads.clone()
+    }
+
+    fn focus(&mut self) -> FocusState {
+        match self.due_frame() {
+            Some(frame) => frame.focus,
+            None if self.focused => FocusState::Focused,
+            None => FocusState::Unfocused,
+        }
+    }
+
+    fn connection_changes(&mut self) -> Vec<ConnectionChange> {
+        if self.reported > self.next {
+            return vec![];
+        }
+        let changes = self.due_frame().map_or_else(Vec::new, |frame| frame.connections.clone());
+        if !changes.is_empty() {
+            self.reported = self.next + 1;
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::mock::MockSource;
+    use ::monitor::Monitor;
+
+    fn pad(index: i32) -> GamepadDescription {
+        GamepadDescription::new(index, format!("054c-05c4-Pad {}", index), GamepadMappingType::Standard, 4, 17)
+    }
+
+    /// Poll everything, both raw and mapped, describing each event.
+    fn drain<S: GamepadSource>(monitor: &mut Monitor<S>) -> Vec<String> {
+        let mut events = vec![];
+        while let Some(event) = monitor.poll() {
+            events.push(format!("{:?} {:?}", event.data, event.map()));
+        }
+        while let Some(event) = monitor.poll_mapped() {
+            events.push(format!("{:?}", event));
+        }
+        events
+    }
+
+    /// Record a session, returning the recording and the events seen at each frame.
+    fn record() -> (Recording, Vec<Vec<String>>) {
+        let mut monitor = Monitor::with_source(Recorder::new(MockSource::new()));
+        let mut frames = vec![];
+
+        monitor.source_mut().source_mut().connect(pad(0));
+        monitor.source_mut().set_time(0.0);
+        frames.push(drain(&mut monitor));
+
+        {
+            let mock = monitor.source_mut().source_mut();
+            mock.press(0, 1);
+            mock.set_axis(0, 0, 0.1 + 0.2);
+            mock.bump_timestamp(0);
+            mock.connect(pad(2));
+        }
+        monitor.source_mut().set_time(16.5);
+        frames.push(drain(&mut monitor));
+
+        {
+            let mock = monitor.source_mut().source_mut();
+            mock.release(0, 1);
+            mock.set_button(2, 7, false, 0.25);
+            mock.bump_timestamp(0);
+            mock.bump_timestamp(2);
+        }
+        monitor.source_mut().set_time(33.0);
+        frames.push(drain(&mut monitor));
+
+        monitor.source_mut().source_mut().disconnect(0);
+        monitor.source_mut().source_mut().resize(1);
+        monitor.source_mut().set_time(50.0);
+        frames.push(drain(&mut monitor));
+
+        {
+            let mock = monitor.source_mut().source_mut();
+            mock.connect(pad(0));
+            mock.press(0, 3);
+            mock.bump_timestamp(0);
+        }
+        monitor.source_mut().set_time(66.0);
+        frames.push(drain(&mut monitor));
+
+        monitor.source_mut().source_mut().set_focus(false);
+        monitor.source_mut().set_time(83.0);
+        frames.push(drain(&mut monitor));
+
+        monitor.source_mut().source_mut().set_focus(true);
+        monitor.source_mut().set_time(100.0);
+        frames.push(drain(&mut monitor));
+
+        (monitor.source().recording().clone(), frames)
+    }
+
+    #[test]
+    fn test_recording_step() {
+        let (recording, frames) = record();
+        assert_eq!(frames[1].len(), 4);
+        assert_eq!(frames[5].last().map(String::as_str), Some("FocusLost None"));
+        assert_eq!(frames[6].last().map(String::as_str), Some("FocusGained None"));
+
+        let mut monitor = Monitor::with_source(ReplaySource::new(recording));
+        assert_eq!(drain(&mut monitor), Vec::<String>::new());
+        for frame in frames {
+            assert!(monitor.source_mut().step());
+            assert_eq!(drain(&mut monitor), frame);
+        }
+        assert!(monitor.source().is_finished());
+        assert!(!monitor.source_mut().step());
+    }
+
+    #[test]
+    fn test_recording_real_time() {
+        let (recording, frames) = record();
+
+        let mut monitor = Monitor::with_source(ReplaySource::new(recording));
+        monitor.source_mut().set_time(25.0);
+        // recorded frames are not merged, even when several are due
+        assert_eq!(drain(&mut monitor), frames[0]);
+        assert_eq!(drain(&mut monitor), frames[1]);
+        assert_eq!(drain(&mut monitor), Vec::<String>::new());
+
+        monitor.source_mut().set_time(200.0);
+        for frame in &frames[2..] {
+            assert_eq!(&drain(&mut monitor), frame);
+        }
+        assert!(monitor.source().is_finished());
+    }
+
+    #[test]
+    fn test_recording_connection_changes() {
+        let mut monitor = Monitor::with_source(Recorder::new(MockSource::new()));
+        monitor.source_mut().source_mut().set_connection_events(true);
+        let mut frames = vec![];
+
+        monitor.source_mut().source_mut().connect(pad(0));
+        frames.push(drain(&mut monitor));
+
+        // a gamepad connecting and disconnecting between snapshots, then the same while unfocused
+        for &focused in [true, false].iter() {
+            let mock = monitor.source_mut().source_mut();
+            mock.set_focus(focused);
+            mock.connect(pad(1));
+            mock.disconnect(1);
+            frames.push(drain(&mut monitor));
+        }
+        monitor.source_mut().source_mut().set_focus(true);
+        frames.push(drain(&mut monitor));
+        assert!(frames[1].contains(&"Connected None".to_string()));
+        assert!(frames[3].contains(&"Disconnected None".to_string()));
+
+        let mut file = vec![];
+        monitor.source().recording().write_to(&mut file).unwrap();
+        let recording = Recording::read_from(&file[..]).unwrap();
+        assert_eq!(&recording, monitor.source().recording());
+
+        let mut monitor = Monitor::with_source(ReplaySource::new(recording));
+        for frame in frames {
+            assert!(monitor.source_mut().step());
+            assert_eq!(drain(&mut monitor), frame);
+        }
+    }
+
+    #[test]
+    fn test_recording_descriptions() {
+        let mut desc = GamepadDescription::new(1, " Pad\\2\r\nline ".into(), GamepadMappingType::NoMapping, 6, 12);
+        desc.rumble = RumbleCapabilities {
+            dual_rumble: true,
+            trigger_rumble: false,
+            pulse: true,
+        };
+        let mut recorder = Recorder::new(MockSource::new());
+        recorder.source_mut().connect(desc.clone());
+        recorder.snapshot();
+
+        let mut file = vec![];
+        recorder.recording().write_to(&mut file).unwrap();
+        assert_eq!(String::from_utf8(file.clone()).unwrap(),
+            "gamepad-web recording 3\nframe 0\nslots 2\nconnect 1 none 6 12 5  Pad\\\\2\\r\\nline \ntimestamp 1 0\n");
+
+        let mut replay = ReplaySource::new(Recording::read_from(&file[..]).unwrap());
+        replay.step();
+        let pads = replay.snapshot();
+        assert_eq!(pads[1].as_ref().map(|(desc, _)| desc), Some(&desc));
+
+        // older recordings have no rumble capabilities, and names are not escaped
+        let old = Recording::read_from(&b"gamepad-web recording 2\nframe 0\nconnect 0 standard 4 17 Pad\\n \n"[..]).unwrap();
+        let mut replay = ReplaySource::new(old);
+        replay.step();
+        assert_eq!(replay.snapshot()[0].as_ref().map(|(desc, _)| desc.name.as_str()), Some("Pad\\n"));
+    }
+
+    #[test]
+    fn test_recording_file() {
+        let (recording, _) = record();
+
+        let mut file = vec![];
+        recording.write_to(&mut file).unwrap();
+        let text = String::from_utf8(file.clone()).unwrap();
+        assert!(text.starts_with("gamepad-web recording 3\nframe 0\nslots 1\nconnect 0 standard 4 17 0 054c-05c4-Pad 0\n"));
+
+        let loaded = Recording::read_from(&file[..]).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(loaded.start_time(), 0.0);
+        assert_eq!(loaded.end_time(), 100.0);
+        assert!(text.contains("frame 83\nfocus unfocused\nframe 100\nfocus refocused\n"));
+
+        match Recording::read_from(&b"gamepad-web recording 4\n"[..]) {
+            Err(RecordingError::UnsupportedVersion(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        match Recording::read_from(&b"gamepad-web recording 1\nframe 0\naxis 0 1\n"[..]) {
+            Err(RecordingError::InvalidLine(3, _)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        match Recording::read_from(&b"gamepad-web recording 1\nslots 1\n"[..]) {
+            Err(RecordingError::InvalidLine(2, _)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // names are escaped, and a bad escape is invalid
+        match Recording::read_from(&b"gamepad-web recording 3\nframe 0\nconnect 0 standard 4 17 0 Pad\\t\n"[..]) {
+            Err(RecordingError::InvalidLine(3, _)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // well formed, but referring to missing gamepads, axes, or buttons
+        for &(text, line) in [
+            ("frame 0\ndisconnect 5\n", 3),
+            ("frame 0\nslots 1\ntimestamp 1 5\n", 4),
+            ("frame 0\nconnect 0 standard 4 17 Pad\naxis 0 4 0.5\n", 4),
+            ("frame 0\nconnect 0 standard 4 17 Pad\nbutton 0 16 1\nvalue 0 17 1\n", 5),
+            ("frame 0\nconnect 1 standard 4 17 Pad\nframe 1\nslots 1\nbutton 1 0 1\n", 6),
+        ].iter() {
+            match Recording::read_from(format!("gamepad-web recording 2\n{}", text).as_bytes()) {
+                Err(RecordingError::InvalidLine(number, _)) => assert_eq!(number, line, "{}", text),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+}
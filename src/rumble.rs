@@ -0,0 +1,389 @@
+use ::monitor::Monitor;
+use ::source::GamepadSource;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+
+/// The rumble effects a gamepad supports, as reported by the browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RumbleCapabilities {
+    /// Strong (low frequency) and weak (high frequency) motors,
+    /// through `vibrationActuator.playEffect("dual-rumble")`.
+    pub dual_rumble: bool,
+    /// Motors in the triggers, and the dual-rumble motors,
+    /// through `vibrationActuator.playEffect("trigger-rumble")`.
+    pub trigger_rumble: bool,
+    /// A single motor, through the older `hapticActuators[0].pulse()`.
+    pub pulse: bool,
+}
+
+/// How a source plays an effect on a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RumbleMethod {
+    DualRumble,
+    TriggerRumble,
+    Pulse,
+}
+
+impl RumbleMethod {
+    /// The effect type passed to `vibrationActuator.playEffect()`, or `None` for `pulse()`.
+    #[cfg(any(feature = "stdweb", feature = "web_sys"))]
+    pub fn effect_type(self) -> Option<&'static str> {
+        match self {
+            RumbleMethod::DualRumble => Some("dual-rumble"),
+            RumbleMethod::TriggerRumble => Some("trigger-rumble"),
+            RumbleMethod::Pulse => None,
+        }
+    }
+}
+
+impl RumbleCapabilities {
+
+    /// Can the gamepad rumble at all?
+    pub fn any(&self) -> bool {
+        self.dual_rumble || self.trigger_rumble || self.pulse
+    }
+
+    /// Choose the best way to play an effect, or `None` if the gamepad cannot rumble.
+    ///
+    /// Trigger magnitudes are dropped without trigger rumble,
+    /// and a single pulse uses the [strongest](struct.RumbleEffect.html#method.magnitude) magnitude.
+    pub(crate) fn method(&self, effect: &RumbleEffect) -> Option<RumbleMethod> {
+        if self.trigger_rumble && (effect.uses_triggers() || !self.dual_rumble) {
+            Some(RumbleMethod::TriggerRumble)
+        }
+        else if self.dual_rumble {
+            Some(RumbleMethod::DualRumble)
+        }
+        else if self.pulse {
+            Some(RumbleMethod::Pulse)
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// A rumble effect, with magnitudes from 0.0 (off) to 1.0 (full strength).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RumbleEffect {
+    /// How long the effect plays, in milliseconds.
+    pub duration: f64,
+    /// How long to wait before playing, in milliseconds.
+    pub start_delay: f64,
+    /// The magnitude of the strong, low frequency motor.
+    pub strong_magnitude: f64,
+    /// The magnitude of the weak, high frequency motor.
+    pub weak_magnitude: f64,
+    /// The magnitude of the left trigger's motor, if the gamepad supports trigger rumble.
+    pub left_trigger: f64,
+    /// The magnitude of the right trigger's motor, if the gamepad supports trigger rumble.
+    pub right_trigger: f64,
+}
+
+impl RumbleEffect {
+
+    /// Rumble the strong and weak motors.
+    pub fn dual(duration: f64, strong_magnitude: f64, weak_magnitude: f64) -> Self {
+        Self {
+            duration,
+            strong_magnitude,
+            weak_magnitude,
+            ..Self::default()
+        }
+    }
+
+    /// Rumble the triggers' motors.
+    pub fn triggers(duration: f64, left_trigger: f64, right_trigger: f64) -> Self {
+        Self {
+            duration,
+            left_trigger,
+            right_trigger,
+            ..Self::default()
+        }
+    }
+
+    /// No rumble, for a pause in a [RumblePattern](struct.RumblePattern.html).
+    pub fn silence(duration: f64) -> Self {
+        Self {
+            duration,
+            ..Self::default()
+        }
+    }
+
+    /// Does the effect use the triggers' motors?
+    pub fn uses_triggers(&self) -> bool {
+        self.left_trigger > 0.0 || self.right_trigger > 0.0
+    }
+
+    /// Is every magnitude 0.0?
+    pub fn is_silent(&self) -> bool {
+        self.magnitude() <= 0.0
+    }
+
+    /// The largest magnitude, used by gamepads with a single motor.
+    pub fn magnitude(&self) -> f64 {
+        self.strong_magnitude
+            .max(self.weak_magnitude)
+            .max(self.left_trigger)
+            .max(self.right_trigger)
+    }
+}
+
+/// An error playing a rumble effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RumbleError {
+    /// No gamepad is connected at the index.
+    NotConnected,
+    /// The gamepad, or the gamepad source, cannot rumble.
+    Unsupported,
+}
+
+impl Display for RumbleError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            RumbleError::NotConnected => write!(f, "gamepad not connected"),
+            RumbleError::Unsupported => write!(f, "rumble not supported"),
+        }
+    }
+}
+
+impl Error for RumbleError {}
+
+/// A sequence of rumble effects, each played for its duration, then the next.
+///
+/// Played by a [RumbleSequencer](struct.RumbleSequencer.html).
+/// An effect's [start_delay](struct.RumbleEffect.html#structfield.start_delay) is ignored in a pattern;
+/// use [silence()](struct.RumbleEffect.html#method.silence) for pauses.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RumblePattern {
+    steps: Vec<RumbleEffect>,
+}
+
+impl RumblePattern {
+
+    /// Create an empty pattern.
+    pub fn new() -> Self {
+        Self {
+            steps: vec![],
+        }
+    }
+
+    /// Add an effect to the end of the pattern.
+    pub fn push(&mut self, effect: RumbleEffect) {
+        self.steps.push(effect);
+    }
+
+    /// Add a pause to the end of the pattern, in milliseconds.
+    pub fn push_pause(&mut self, duration: f64) {
+        self.push(RumbleEffect::silence(duration));
+    }
+
+    /// The effects, in order.
+    pub fn steps(&self) -> &[RumbleEffect] {
+        &self.steps
+    }
+
+    /// The total duration, in milliseconds.
+    pub fn duration(&self) -> f64 {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// The step playing at `elapsed` milliseconds after the start, and when it started,
+    /// or `None` once the pattern is finished.
+    fn step_at(&self, elapsed: f64) -> Option<(usize, f64)> {
+        let mut start = 0.0;
+        for (i, step) in self.steps.iter().enumerate() {
+            if elapsed < start + step.duration {
+                return Some((i, start));
+            }
+            start += step.duration;
+        }
+        None
+    }
+}
+
+impl From<Vec<RumbleEffect>> for RumblePattern {
+    fn from(steps: Vec<RumbleEffect>) -> Self {
+        Self {
+            steps,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PlayingPattern {
+    pattern: RumblePattern,
+    start: f64,
+    /// The step most recently played.
+    step: Option<usize>,
+}
+
+/// Plays [RumblePatterns](struct.RumblePattern.html) on gamepads, updated once per frame.
+///
+/// Each step is started by the first [update()](#method.update) at or after its time,
+/// and cut short so the pattern keeps its timing.
+///
+/// ```no_run
+/// use gamepad_web::*;
+///
+/// let mut monitor = Monitor::new();
+/// let mut sequencer = RumbleSequencer::new();
+///
+/// // three short pulses
+/// let mut pattern = RumblePattern::new();
+/// for _ in 0..3 {
+///     pattern.push(RumbleEffect::dual(100.0, 1.0, 0.5));
+///     pattern.push_pause(100.0);
+/// }
+/// # let now = 0.0;
+/// sequencer.play(0, pattern, now);
+///
+/// // each frame
+/// sequencer.update(&mut monitor, now);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RumbleSequencer {
+    playing: HashMap<usize, PlayingPattern>,
+}
+
+impl RumbleSequencer {
+
+    /// Create a sequencer, playing nothing.
+    pub fn new() -> Self {
+        Self {
+            playing: HashMap::new(),
+        }
+    }
+
+    /// Start playing a pattern on the gamepad at this index, at time `now` (in milliseconds),
+    /// replacing any pattern it is playing.
+    ///
+    /// The first step plays at the next [update()](#method.update).
+    pub fn play(&mut self, pad: usize, pattern: RumblePattern, now: f64) {
+        self.playing.insert(pad, PlayingPattern {
+            pattern,
+            start: now,
+            step: None,
+        });
+    }
+
+    /// Stop the pattern playing on the gamepad at this index, and its rumble.
+    pub fn stop<S: GamepadSource>(&mut self, monitor: &mut Monitor<S>, pad: usize) {
+        if self.playing.remove(&pad).is_some() {
+            let _ = monitor.stop_rumble(pad);
+        }
+    }
+
+    /// Is a pattern playing on the gamepad at this index?
+    pub fn is_playing(&self, pad: usize) -> bool {
+        self.playing.contains_key(&pad)
+    }
+
+    /// Play the steps due at time `now` (in milliseconds).
+    ///
+    /// Patterns are dropped once finished, or if their gamepad cannot rumble.
+    pub fn update<S: GamepadSource>(&mut self, monitor: &mut Monitor<S>, now: f64) {
+        self.playing.retain(|&pad, playing| {
+            let elapsed = now - playing.start;
+            let (step, step_start) = match playing.pattern.step_at(elapsed) {
+                Some(step) => step,
+                None => return false,
+            };
+            if playing.step == Some(step) {
+                return true;
+            }
+            playing.step = Some(step);
+
+            let mut effect = playing.pattern.steps[step];
+            effect.duration -= elapsed - step_start;
+            effect.start_delay = 0.0;
+            let result = if effect.is_silent() {
+                monitor.stop_rumble(pad)
+            }
+            else {
+                monitor.rumble(pad, effect)
+            };
+            result.is_ok()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::{
+        GamepadDescription,
+        GamepadMappingType,
+    };
+    use ::mock::MockSource;
+
+    fn rumble_pad(index: i32, rumble: RumbleCapabilities) -> GamepadDescription {
+        let mut desc = GamepadDescription::new(index, "pad".into(), GamepadMappingType::Standard, 4, 17);
+        desc.rumble = rumble;
+        desc
+    }
+
+    #[test]
+    fn test_rumble_method() {
+        let dual = RumbleEffect::dual(100.0, 1.0, 0.5);
+        let triggers = RumbleEffect::triggers(100.0, 0.0, 0.75);
+        assert_eq!(triggers.magnitude(), 0.75);
+
+        let caps = RumbleCapabilities { dual_rumble: true, trigger_rumble: true, pulse: true };
+        assert_eq!(caps.method(&dual), Some(RumbleMethod::DualRumble));
+        assert_eq!(caps.method(&triggers), Some(RumbleMethod::TriggerRumble));
+
+        let caps = RumbleCapabilities { pulse: true, ..RumbleCapabilities::default() };
+        assert_eq!(caps.method(&triggers), Some(RumbleMethod::Pulse));
+        assert_eq!(RumbleCapabilities::default().method(&dual), None);
+    }
+
+    #[test]
+    fn test_rumble_sequencer() {
+        let mut monitor = Monitor::with_source(MockSource::new());
+        let dual = RumbleCapabilities { dual_rumble: true, ..RumbleCapabilities::default() };
+        monitor.source_mut().connect(rumble_pad(0, dual));
+        monitor.source_mut().connect(rumble_pad(1, RumbleCapabilities::default()));
+        while monitor.poll().is_some() {}
+
+        let mut pattern = RumblePattern::new();
+        pattern.push(RumbleEffect::dual(100.0, 1.0, 0.5));
+        pattern.push_pause(50.0);
+        pattern.push(RumbleEffect::dual(100.0, 0.25, 0.0));
+        assert_eq!(pattern.duration(), 250.0);
+
+        let mut sequencer = RumbleSequencer::new();
+        sequencer.play(0, pattern.clone(), 1000.0);
+        sequencer.play(1, pattern, 1000.0);
+
+        sequencer.update(&mut monitor, 1000.0);
+        sequencer.update(&mut monitor, 1016.0);
+        // late by 10ms, so cut short
+        sequencer.update(&mut monitor, 1110.0);
+        sequencer.update(&mut monitor, 1160.0);
+        assert_eq!(monitor.source().rumble_history(0), &[
+            Some(RumbleEffect::dual(100.0, 1.0, 0.5)),
+            None,
+            Some(RumbleEffect::dual(90.0, 0.25, 0.0)),
+        ]);
+        // the pad without rumble was dropped
+        assert!(!sequencer.is_playing(1));
+        assert!(monitor.source().rumble_history(1).is_empty());
+
+        assert!(sequencer.is_playing(0));
+        sequencer.update(&mut monitor, 1250.0);
+        assert!(!sequencer.is_playing(0));
+
+        sequencer.play(0, RumblePattern::from(vec![RumbleEffect::dual(100.0, 1.0, 1.0)]), 2000.0);
+        sequencer.update(&mut monitor, 2000.0);
+        sequencer.stop(&mut monitor, 0);
+        assert_eq!(monitor.source().rumble_history(0)[3..], [Some(RumbleEffect::dual(100.0, 1.0, 1.0)), None]);
+    }
+}
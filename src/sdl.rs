@@ -0,0 +1,604 @@
+use ::gamepad::GamepadDescription;
+use ::mapping::{
+    Axis,
+    Button,
+    Mapping,
+};
+
+use std::error::Error;
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// A small selection of mappings, in the format of SDL's `gamecontrollerdb.txt`.
+const BUNDLED_DATABASE: &str = include_str!("gamecontrollerdb.txt");
+
+/// Half of an axis's range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AxisHalf {
+    /// [-1–0], written `-a0` in SDL mappings.
+    Negative,
+    /// [0–1], written `+a0` in SDL mappings.
+    Positive,
+}
+
+/// A raw gamepad input, as written in an SDL mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SdlInput {
+    /// A button index, `b0`.
+    Button(usize),
+    /// An axis index, `a0`, optionally limited to half its range (`+a0`, `-a0`) or inverted (`a0~`).
+    Axis {
+        /// The axis index.
+        index: usize,
+        /// The half of the range used, if not the full range.
+        half: Option<AxisHalf>,
+        /// Is the axis reversed?
+        inverted: bool,
+    },
+    /// A hat direction, `h0.1`: the hat index, and the direction bit (1 up, 2 right, 4 down, 8 left).
+    Hat(usize, u8),
+}
+
+/// A standard button or axis, as named in an SDL mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SdlTarget {
+    /// A button, such as `a` or `dpup`. Also the triggers, `lefttrigger` and `righttrigger`.
+    Button(Button),
+    /// An axis, such as `leftx`, optionally limited to half its range (`+leftx`, `-leftx`).
+    Axis(Axis, Option<AxisHalf>),
+}
+
+/// One element of an SDL mapping: a standard target, bound to a raw input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SdlBinding {
+    /// The standard button or axis.
+    pub target: SdlTarget,
+    /// The raw input it is read from.
+    pub input: SdlInput,
+}
+
+/// An error in an SDL mapping string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SdlParseError {
+    /// The GUID is not 32 hexadecimal digits.
+    InvalidGuid(String),
+    /// The mapping has no name field.
+    MissingName,
+    /// An element is not a `name:input` pair, or its input is malformed.
+    InvalidElement(String),
+}
+
+impl Display for SdlParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SdlParseError::InvalidGuid(ref guid) => write!(f, "invalid SDL mapping GUID \"{}\"", guid),
+            SdlParseError::MissingName => write!(f, "SDL mapping has no name"),
+            SdlParseError::InvalidElement(ref element) => write!(f, "invalid SDL mapping element \"{}\"", element),
+        }
+    }
+}
+
+impl Error for SdlParseError {}
+
+/// The SDL name of each button.
+const BUTTON_NAMES: &[(&str, Button)] = &[
+    ("a", Button::South),
+    ("b", Button::East),
+    ("x", Button::West),
+    ("y", Button::North),
+    ("leftshoulder", Button::LT1),
+    ("rightshoulder", Button::RT1),
+    ("lefttrigger", Button::LT2),
+    ("righttrigger", Button::RT2),
+    ("back", Button::Select),
+    ("start", Button::Start),
+    ("leftstick", Button::LeftStick),
+    ("rightstick", Button::RightStick),
+    ("dpup", Button::Up),
+    ("dpdown", Button::Down),
+    ("dpleft", Button::Left),
+    ("dpright", Button::Right),
+    ("guide", Button::Home),
+];
+
+/// The SDL name of each axis.
+const AXIS_NAMES: &[(&str, Axis)] = &[
+    ("leftx", Axis::LeftStickX),
+    ("lefty", Axis::LeftStickY),
+    ("rightx", Axis::RightStickX),
+    ("righty", Axis::RightStickY),
+    // only written: SDL reads these names as buttons
+    ("lefttrigger", Axis::LeftTrigger),
+    ("righttrigger", Axis::RightTrigger),
+];
+
+fn parse_target(name: &str) -> Option<SdlTarget> {
+    let (half, name) = match name.chars().next() {
+        Some('+') => (Some(AxisHalf::Positive), &name[1..]),
+        Some('-') => (Some(AxisHalf::Negative), &name[1..]),
+        _ => (None, name),
+    };
+
+    if let Some(&(_, button)) = BUTTON_NAMES.iter().find(|&&(button_name, _)| button_name == name) {
+        return match half {
+            None => Some(SdlTarget::Button(button)),
+            Some(_) => None,
+        };
+    }
+
+    AXIS_NAMES.iter()
+        .find(|&&(axis_name, _)| axis_name == name)
+        .map(|&(_, axis)| SdlTarget::Axis(axis, half))
+}
+
+impl Display for AxisHalf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            AxisHalf::Negative => write!(f, "-"),
+            AxisHalf::Positive => write!(f, "+"),
+        }
+    }
+}
+
+/// Written as in an SDL mapping, such as `-leftx` or `dpup`.
+impl Display for SdlTarget {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SdlTarget::Button(button) => {
+                let &(name, _) = BUTTON_NAMES.iter().find(|&&(_, named)| named == button).expect("unnamed button");
+                write!(f, "{}", name)
+            },
+            SdlTarget::Axis(axis, half) => {
+                let &(name, _) = AXIS_NAMES.iter().find(|&&(_, named)| named == axis).expect("unnamed axis");
+                if let Some(half) = half {
+                    write!(f, "{}", half)?;
+                }
+                write!(f, "{}", name)
+            },
+        }
+    }
+}
+
+/// Written as in an SDL mapping, such as `b0`, `+a2~`, or `h0.4`.
+impl Display for SdlInput {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SdlInput::Button(index) => write!(f, "b{}", index),
+            SdlInput::Axis { index, half, inverted } => {
+                if let Some(half) = half {
+                    write!(f, "{}", half)?;
+                }
+                write!(f, "a{}{}", index, if inverted { "~" } else { "" })
+            },
+            SdlInput::Hat(index, mask) => write!(f, "h{}.{}", index, mask),
+        }
+    }
+}
+
+fn parse_input(input: &str) -> Option<SdlInput> {
+    let (half, input) = match input.chars().next() {
+        Some('+') => (Some(AxisHalf::Positive), &input[1..]),
+        Some('-') => (Some(AxisHalf::Negative), &input[1..]),
+        _ => (None, input),
+    };
+    let (inverted, input) = match input.strip_suffix('~') {
+        Some(input) => (true, input),
+        None => (false, input),
+    };
+
+    let kind = input.chars().next()?;
+    let rest = &input[kind.len_utf8()..];
+    match kind {
+        'b' if half.is_none() && !inverted =>
+            rest.parse().ok().map(SdlInput::Button),
+        'a' =>
+            rest.parse().ok().map(|index| SdlInput::Axis { index, half, inverted }),
+        'h' if half.is_none() && !inverted => {
+            let mut parts = rest.splitn(2, '.');
+            let hat = parts.next()?.parse().ok()?;
+            let mask = parts.next()?.parse().ok()?;
+            Some(SdlInput::Hat(hat, mask))
+        },
+        _ => None,
+    }
+}
+
+/// An SDL GUID for a USB device with these vendor and product IDs.
+pub(crate) fn ids_guid(vendor_id: u16, product_id: u16) -> String {
+    format!("03000000{:02x}{:02x}0000{:02x}{:02x}000000000000",
+        vendor_id & 0xff, vendor_id >> 8, product_id & 0xff, product_id >> 8)
+}
+
+/// The vendor and product IDs in an SDL GUID, if it has them.
+///
+/// The GUID holds little-endian 16-bit words: bus, CRC, vendor, 0, product, 0, version, driver.
+fn guid_ids(guid: &str) -> Option<(u16, u16)> {
+    if guid.len() != 32 || !guid.is_ascii() {
+        return None;
+    }
+    let word = |i: usize| {
+        let bytes = &guid[i * 4..i * 4 + 4];
+        u16::from_str_radix(&bytes[2..4], 16).ok()
+            .and_then(|high| u16::from_str_radix(&bytes[..2], 16).ok().map(|low| (high << 8) | low))
+    };
+
+    let (vendor, product) = (word(2)?, word(4)?);
+    if word(3)? != 0 || word(5)? != 0 || vendor == 0 {
+        return None;
+    }
+    Some((vendor, product))
+}
+
+/// How far an axis input is pressed [0–1], read as a button.
+///
+/// A full axis is read from -1.0 to 1.0, and a half axis from 0.0 to either end.
+pub(crate) fn axis_amount(value: f64, half: Option<AxisHalf>, inverted: bool) -> f64 {
+    let value = if inverted { 0.0 - value } else { value };
+    match half {
+        None => (value + 1.0) / 2.0,
+        Some(AxisHalf::Positive) => value.max(0.0),
+        Some(AxisHalf::Negative) => (0.0 - value).max(0.0),
+    }
+}
+
+/// A gamepad mapping, parsed from SDL's `gamecontrollerdb.txt` format.
+///
+/// For example, `030000005e0400008e02000010010000,Xbox 360 Controller,a:b0,b:b1,leftx:a0,dpup:h0.1,lefttrigger:a2,platform:Linux,`
+///
+/// As a [Mapping](trait.Mapping.html), it relates buttons bound to button inputs,
+/// and full axes bound to full, non-inverted axis inputs.
+/// Buttons bound to axes, such as analog triggers, are mapped by
+/// [map_axis_to_button()](#method.map_axis_to_button).
+/// Other bindings, such as hats and half axes, are available through [bindings](#structfield.bindings).
+/// Events of gamepads using this mapping are [mapped](struct.Event.html#method.map) through all of its bindings,
+/// like a [MappingTable](struct.MappingTable.html), except hats:
+/// SDL numbers hats apart from axes, and browsers don't report them the same way.
+/// Unknown element names (such as `misc1`) are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SdlMapping {
+    /// The 32-digit hexadecimal GUID, identifying the device.
+    pub guid: String,
+    /// The device name.
+    pub name: String,
+    /// The platform this mapping was made on, such as "Linux" or "Windows".
+    pub platform: Option<String>,
+    /// The binding of each standard button and axis.
+    pub bindings: Vec<SdlBinding>,
+}
+
+impl SdlMapping {
+
+    /// The USB vendor and product IDs in this mapping's GUID, if it has them.
+    pub fn ids(&self) -> Option<(u16, u16)> {
+        guid_ids(&self.guid)
+    }
+
+    /// Map an axis value to a button bound to that axis, such as an analog trigger.
+    ///
+    /// Returns the button, and its value [0–1].
+    pub fn map_axis_to_button(&self, index: usize, value: f64) -> Option<(Button, f64)> {
+        self.bindings.iter()
+            .filter_map(|binding| match (binding.target, binding.input) {
+                (SdlTarget::Button(button), SdlInput::Axis { index: i, half, inverted }) if i == index =>
+                    Some((button, axis_amount(value, half, inverted))),
+                _ => None,
+            })
+            .next()
+    }
+}
+
+/// Written in the format of `gamecontrollerdb.txt`, which [parse()](#impl-FromStr) reads back.
+impl Display for SdlMapping {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{},{},", self.guid, self.name)?;
+        for binding in &self.bindings {
+            write!(f, "{}:{},", binding.target, binding.input)?;
+        }
+        if let Some(ref platform) = self.platform {
+            write!(f, "platform:{},", platform)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SdlMapping {
+    type Err = SdlParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut fields = line.trim().split(',');
+
+        let guid = fields.next().unwrap_or("").trim();
+        if guid.len() != 32 || !guid.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(SdlParseError::InvalidGuid(guid.into()));
+        }
+
+        let name = fields.next().ok_or(SdlParseError::MissingName)?.trim();
+
+        let mut platform = None;
+        let mut bindings = vec![];
+        for element in fields.map(str::trim).filter(|element| !element.is_empty()) {
+            let mut parts = element.splitn(2, ':');
+            let key = parts.next().unwrap_or("");
+            let value = parts.next().ok_or_else(|| SdlParseError::InvalidElement(element.into()))?;
+
+            if key == "platform" {
+                platform = Some(value.into());
+                continue;
+            }
+
+            let target = match parse_target(key) {
+                Some(target) => target,
+                None => continue,
+            };
+            let input = parse_input(value).ok_or_else(|| SdlParseError::InvalidElement(element.into()))?;
+            bindings.push(SdlBinding { target, input });
+        }
+
+        Ok(Self {
+            guid: guid.to_lowercase(),
+            name: name.into(),
+            platform,
+            bindings,
+        })
+    }
+}
+
+impl Mapping for SdlMapping {
+    fn map_button(&self, index: usize) -> Option<Button> {
+        self.bindings.iter()
+            .filter(|binding| binding.input == SdlInput::Button(index))
+            .filter_map(|binding| match binding.target {
+                SdlTarget::Button(button) => Some(button),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn button_index(&self, button: Button) -> Option<usize> {
+        self.bindings.iter()
+            .filter(|binding| binding.target == SdlTarget::Button(button))
+            .filter_map(|binding| match binding.input {
+                SdlInput::Button(index) => Some(index),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn map_axis(&self, index: usize) -> Option<Axis> {
+        let input = SdlInput::Axis { index, half: None, inverted: false };
+        self.bindings.iter()
+            .filter(|binding| binding.input == input)
+            .filter_map(|binding| match binding.target {
+                SdlTarget::Axis(axis, None) => Some(axis),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn axis_index(&self, axis: Axis) -> Option<usize> {
+        self.bindings.iter()
+            .filter(|binding| binding.target == SdlTarget::Axis(axis, None))
+            .filter_map(|binding| match binding.input {
+                SdlInput::Axis { index, half: None, inverted: false } => Some(index),
+                _ => None,
+            })
+            .next()
+    }
+}
+
+/// A collection of [SdlMappings](struct.SdlMapping.html), looked up by device.
+#[derive(Debug, Clone, Default)]
+pub struct SdlDatabase {
+    mappings: Vec<Rc<SdlMapping>>,
+    platform: Option<String>,
+}
+
+impl SdlDatabase {
+
+    /// Create an empty database.
+    pub fn new() -> Self {
+        Self {
+            mappings: vec![],
+            platform: None,
+        }
+    }
+
+    /// A database of common gamepads, bundled with this crate.
+    ///
+    /// It is a small selection; for wider coverage, add mappings from
+    /// [SDL_GameControllerDB](https://github.com/gabomdq/SDL_GameControllerDB).
+    pub fn bundled() -> Self {
+        let mut db = SdlDatabase::new();
+        db.add_mappings(BUNDLED_DATABASE).expect("invalid bundled SDL mappings");
+        db
+    }
+
+    /// Add mappings, one per line, in the format of `gamecontrollerdb.txt`.
+    ///
+    /// Blank lines and comments (starting with `#`) are skipped.
+    /// Later mappings take priority over earlier mappings for the same device.
+    /// If any line is invalid, no mappings are added.
+    pub fn add_mappings(&mut self, text: &str) -> Result<(), SdlParseError> {
+        let mappings = text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.parse().map(Rc::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.mappings.extend(mappings);
+        Ok(())
+    }
+
+    /// Add a mapping, taking priority over earlier mappings for the same device.
+    pub fn add(&mut self, mapping: SdlMapping) {
+        self.mappings.push(Rc::new(mapping));
+    }
+
+    /// Prefer mappings made on this platform, such as "Linux" or "Windows".
+    ///
+    /// Raw button and axis indices of the same device often differ between platforms.
+    pub fn set_platform(&mut self, platform: Option<&str>) {
+        self.platform = platform.map(String::from);
+    }
+
+    /// The number of mappings.
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Are there no mappings?
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Find the mapping for this USB vendor and product.
+    pub fn lookup_ids(&self, vendor_id: u16, product_id: u16) -> Option<&Rc<SdlMapping>> {
+        let matches = || self.mappings.iter()
+            .rev()
+            .filter(move |mapping| mapping.ids() == Some((vendor_id, product_id)));
+
+        let preferred = self.platform.as_ref().and_then(|platform|
+            matches().find(|mapping| mapping.platform.as_ref() == Some(platform))
+        );
+        preferred.or_else(|| matches().next())
+    }
+
+    /// Find the mapping for a gamepad, by its [identity](struct.GamepadDescription.html#method.identity).
+    pub fn lookup(&self, desc: &GamepadDescription) -> Option<&Rc<SdlMapping>> {
+        desc.identity().and_then(|identity| self.lookup_ids(identity.vendor_id, identity.product_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::GamepadMappingType;
+
+    const XBOX_360: &str = "030000005e0400008e02000010010000,Xbox 360 Controller,a:b0,b:b1,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b8,leftshoulder:b4,leftstick:b9,lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b10,righttrigger:a5,rightx:a3,righty:a4,start:b7,x:b2,y:b3,platform:Linux,";
+
+    #[test]
+    fn test_sdl_parse() {
+        let mapping: SdlMapping = XBOX_360.parse().unwrap();
+        assert_eq!(mapping.name, "Xbox 360 Controller");
+        assert_eq!(mapping.platform, Some("Linux".into()));
+        assert_eq!(mapping.ids(), Some((0x045e, 0x028e)));
+        assert_eq!(mapping.bindings.len(), 21);
+
+        assert!(mapping.bindings.contains(&SdlBinding {
+            target: SdlTarget::Button(Button::Up),
+            input: SdlInput::Hat(0, 1),
+        }));
+        assert!(mapping.bindings.contains(&SdlBinding {
+            target: SdlTarget::Button(Button::LT2),
+            input: SdlInput::Axis { index: 2, half: None, inverted: false },
+        }));
+    }
+
+    #[test]
+    fn test_sdl_write() {
+        let mapping: SdlMapping = XBOX_360.parse().unwrap();
+        assert_eq!(mapping.to_string(), XBOX_360);
+
+        let mapping: SdlMapping = "03000000000000000000000000000000,Pad,-leftx:+a2~,".parse().unwrap();
+        assert_eq!(mapping.to_string(), "03000000000000000000000000000000,Pad,-leftx:+a2~,");
+
+        assert_eq!(guid_ids(&ids_guid(0x045e, 0x028e)), Some((0x045e, 0x028e)));
+        assert_eq!(&ids_guid(0x045e, 0x028e)[..20], &XBOX_360[..20]);
+    }
+
+    #[test]
+    fn test_sdl_parse_inputs() {
+        assert_eq!(parse_input("b12"), Some(SdlInput::Button(12)));
+        assert_eq!(parse_input("a3"), Some(SdlInput::Axis { index: 3, half: None, inverted: false }));
+        assert_eq!(parse_input("+a2"), Some(SdlInput::Axis { index: 2, half: Some(AxisHalf::Positive), inverted: false }));
+        assert_eq!(parse_input("-a1~"), Some(SdlInput::Axis { index: 1, half: Some(AxisHalf::Negative), inverted: true }));
+        assert_eq!(parse_input("h1.8"), Some(SdlInput::Hat(1, 8)));
+        assert_eq!(parse_input("+b1"), None);
+        assert_eq!(parse_input("h1"), None);
+        assert_eq!(parse_input("q1"), None);
+        assert_eq!(parse_input(""), None);
+
+        assert_eq!(parse_target("-leftx"), Some(SdlTarget::Axis(Axis::LeftStickX, Some(AxisHalf::Negative))));
+        assert_eq!(parse_target("+a"), None);
+        assert_eq!(parse_target("paddle1"), None);
+    }
+
+    #[test]
+    fn test_sdl_parse_errors() {
+        assert_eq!("xyz,Pad,a:b0".parse::<SdlMapping>(), Err(SdlParseError::InvalidGuid("xyz".into())));
+        assert_eq!("030000005e0400008e02000010010000".parse::<SdlMapping>(), Err(SdlParseError::MissingName));
+        assert_eq!("030000005e0400008e02000010010000,Pad,a".parse::<SdlMapping>(),
+            Err(SdlParseError::InvalidElement("a".into())));
+        assert_eq!("030000005e0400008e02000010010000,Pad,a:z0".parse::<SdlMapping>(),
+            Err(SdlParseError::InvalidElement("a:z0".into())));
+
+        // unknown elements are skipped
+        let mapping: SdlMapping = "030000005e0400008e02000010010000,Pad,misc1:b15,a:b0,".parse().unwrap();
+        assert_eq!(mapping.bindings.len(), 1);
+    }
+
+    #[test]
+    fn test_sdl_mapping() {
+        let mapping: SdlMapping = XBOX_360.parse().unwrap();
+
+        assert_eq!(mapping.map_button(0), Some(Button::South));
+        assert_eq!(mapping.map_button(8), Some(Button::Home));
+        assert_eq!(mapping.map_button(11), None);
+        assert_eq!(mapping.button_index(Button::Start), Some(7));
+        // bound to an axis or hat, not a button
+        assert_eq!(mapping.button_index(Button::LT2), None);
+        assert_eq!(mapping.button_index(Button::Up), None);
+
+        assert_eq!(mapping.map_axis(3), Some(Axis::RightStickX));
+        assert_eq!(mapping.map_axis(2), None);
+        assert_eq!(mapping.axis_index(Axis::RightStickY), Some(4));
+
+        assert_eq!(mapping.map_axis_to_button(2, -1.0), Some((Button::LT2, 0.0)));
+        assert_eq!(mapping.map_axis_to_button(5, 0.5), Some((Button::RT2, 0.75)));
+        assert_eq!(mapping.map_axis_to_button(0, 0.5), None);
+
+        let mapping: SdlMapping = "030000005e0400008e02000010010000,Pad,lefttrigger:+a2,righttrigger:-a2~".parse().unwrap();
+        assert_eq!(mapping.map_axis_to_button(2, 0.5), Some((Button::LT2, 0.5)));
+        assert_eq!(mapping.map_axis_to_button(2, -0.5), Some((Button::LT2, 0.0)));
+
+        // GUIDs without IDs, or of the wrong length
+        assert_eq!(mapping.ids(), Some((0x045e, 0x028e)));
+        for guid in ["", "030000005e04", "030000005e0400008e0200001001000000", "030000005e0400008e020000100100é"].iter() {
+            let mapping = SdlMapping { guid: guid.to_string(), ..mapping.clone() };
+            assert_eq!(mapping.ids(), None);
+        }
+    }
+
+    #[test]
+    fn test_sdl_database() {
+        let db = SdlDatabase::bundled();
+        assert!(!db.is_empty());
+
+        let xbox = GamepadDescription::new(0, "045e-028e-Microsoft X-Box 360 pad".into(), GamepadMappingType::NoMapping, 8, 11);
+        assert_eq!(db.lookup(&xbox).unwrap().name, "Xbox 360 Controller");
+
+        let unknown = GamepadDescription::new(0, "1234-5678-Unknown".into(), GamepadMappingType::NoMapping, 2, 2);
+        assert_eq!(db.lookup(&unknown), None);
+
+        let mut db = SdlDatabase::new();
+        db.add_mappings(&format!("# comment\n\n{}\n", XBOX_360)).unwrap();
+        db.add_mappings(&XBOX_360.replace("platform:Linux", "platform:Windows").replace("a:b0", "a:b1")).unwrap();
+        assert_eq!(db.len(), 2);
+        assert!(db.add_mappings("not a mapping").is_err());
+        assert_eq!(db.len(), 2);
+
+        // later mappings win, unless the platform is preferred
+        assert_eq!(db.lookup_ids(0x045e, 0x028e).unwrap().platform, Some("Windows".into()));
+        db.set_platform(Some("Linux"));
+        assert_eq!(db.lookup_ids(0x045e, 0x028e).unwrap().platform, Some("Linux".into()));
+    }
+}
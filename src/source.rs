@@ -0,0 +1,129 @@
+use ::gamepad::{
+    GamepadDescription,
+    GamepadState,
+};
+use ::rumble::{
+    RumbleEffect,
+    RumbleError,
+};
+
+#[cfg(any(test, feature = "testing", feature = "stdweb", feature = "web_sys"))]
+use std::cell::Cell;
+
+/// Whether the page has input focus, as reported by a [GamepadSource](trait.GamepadSource.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FocusState {
+    /// The page is visible and focused, and has been since the previous check.
+    Focused,
+    /// The page is hidden or unfocused.
+    Unfocused,
+    /// The page is visible and focused, but lost focus since the previous check.
+    ///
+    /// Browsers stop animation frames while a page is hidden,
+    /// so the whole loss of focus may happen between two checks.
+    Refocused,
+}
+
+/// A connection or disconnection reported by the browser as it happened,
+/// from its `gamepadconnected` or `gamepaddisconnected` events.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionChange {
+    /// A gamepad connected, in this state.
+    Connected(GamepadDescription, GamepadState),
+    /// The gamepad at this index disconnected.
+    Disconnected(usize),
+}
+
+/// Tracks focus changes between checks, for sources with a page.
+///
+/// Uses `Cell`s, so browser event listeners can share it through an `Rc`.
+#[cfg(any(test, feature = "testing", feature = "stdweb", feature = "web_sys"))]
+#[derive(Debug, Clone)]
+pub(crate) struct FocusTracker {
+    focused: Cell<bool>,
+    lost: Cell<bool>,
+}
+
+#[cfg(any(test, feature = "testing", feature = "stdweb", feature = "web_sys"))]
+impl FocusTracker {
+    pub fn new() -> Self {
+        Self {
+            focused: Cell::new(true),
+            lost: Cell::new(false),
+        }
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.set(focused);
+        if !focused {
+            self.lost.set(true);
+        }
+    }
+
+    /// The focus since the previous check.
+    pub fn check(&self) -> FocusState {
+        if !self.focused.get() {
+            FocusState::Unfocused
+        }
+        else if self.lost.replace(false) {
+            FocusState::Refocused
+        }
+        else {
+            FocusState::Focused
+        }
+    }
+}
+
+/// A provider of raw gamepad snapshots, polled by a [Monitor](struct.Monitor.html).
+///
+/// The monitor compares successive snapshots to find connections, disconnections, and input changes,
+/// so a source only needs to report the current state of each gamepad.
+pub trait GamepadSource {
+    /// Take a snapshot of every gamepad slot.
+    ///
+    /// A slot's position in the returned list is its gamepad index.
+    /// Empty slots (no gamepad connected) are `None`.
+    ///
+    /// The monitor skips a gamepad whose [timestamp](struct.GamepadState.html#method.timestamp)
+    /// has not changed since the previous snapshot.
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>>;
+
+    /// Replace `pads` with a snapshot, like [snapshot()](#tymethod.snapshot).
+    ///
+    /// The monitor passes the same buffer to each call, holding an earlier snapshot,
+    /// so a source may update it in place instead of allocating.
+    /// By default, the buffer is replaced by a new snapshot.
+    fn snapshot_into(&mut self, pads: &mut Vec<Option<(GamepadDescription, GamepadState)>>) {
+        *pads = self.snapshot();
+    }
+
+    /// Check whether the page has input focus, before each snapshot.
+    ///
+    /// While unfocused, the monitor releases all input and ignores snapshots.
+    /// Sources without a page are always `Focused`.
+    fn focus(&mut self) -> FocusState {
+        FocusState::Focused
+    }
+
+    /// Take the connections and disconnections reported since the previous call, in order.
+    ///
+    /// The monitor applies these before each snapshot, so a gamepad which connects and disconnects
+    /// between two snapshots is still reported. Changes seen while unfocused are applied when focus returns.
+    /// Changes already seen by the monitor, or seen again by the snapshot, are not reported twice.
+    /// Sources which only take snapshots report none.
+    fn connection_changes(&mut self) -> Vec<ConnectionChange> {
+        vec![]
+    }
+
+    /// Play a rumble effect on the gamepad at this index, replacing any effect it is playing.
+    ///
+    /// Sources without rumble return `Unsupported`.
+    fn rumble(&mut self, _index: usize, _effect: &RumbleEffect) -> Result<(), RumbleError> {
+        Err(RumbleError::Unsupported)
+    }
+
+    /// Stop any rumble effect playing on the gamepad at this index.
+    fn stop_rumble(&mut self, _index: usize) -> Result<(), RumbleError> {
+        Err(RumbleError::Unsupported)
+    }
+}
@@ -0,0 +1,294 @@
+use ::gamepad::{
+    GamepadDescription,
+    GamepadMappingType,
+    GamepadState,
+};
+use ::rumble::{
+    RumbleCapabilities,
+    RumbleEffect,
+    RumbleError,
+};
+use ::source::{
+    ConnectionChange,
+    FocusState,
+    FocusTracker,
+    GamepadSource,
+};
+
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+use stdweb::Value;
+use stdweb::unstable::TryInto;
+use stdweb::web::{
+    self,
+    EventListenerHandle,
+    Gamepad,
+    IEventTarget,
+};
+use stdweb::web::event::{
+    GamepadConnectedEvent,
+    GamepadDisconnectedEvent,
+    IGamepadEvent,
+};
+
+fn mapping_type(mapping: web::GamepadMappingType) -> GamepadMappingType {
+    match mapping {
+        web::GamepadMappingType::NoMapping => GamepadMappingType::NoMapping,
+        web::GamepadMappingType::Standard => GamepadMappingType::Standard,
+    }
+}
+
+/// Read the effects supported by the gamepad's actuators.
+fn rumble_capabilities(pad: &Gamepad) -> RumbleCapabilities {
+    let flags: i32 = js! {
+        var pad = @{pad};
+        var actuator = pad.vibrationActuator;
+        // older browsers report a single effect type
+        var effects = actuator ? (actuator.effects || [actuator.type]) : [];
+        var haptics = pad.hapticActuators;
+        return (effects.indexOf("dual-rumble") >= 0 ? 1 : 0)
+            | (effects.indexOf("trigger-rumble") >= 0 ? 2 : 0)
+            | (haptics && haptics.length > 0 ? 4 : 0);
+    }.try_into().unwrap_or(0);
+
+    RumbleCapabilities {
+        dual_rumble: flags & 1 != 0,
+        trigger_rumble: flags & 2 != 0,
+        pulse: flags & 4 != 0,
+    }
+}
+
+fn description(pad: &Gamepad) -> GamepadDescription {
+    let mut desc = GamepadDescription::new(
+        pad.index(),
+        pad.id(),
+        mapping_type(pad.mapping()),
+        pad.axes().len(),
+        pad.buttons().len(),
+    );
+    desc.rumble = rumble_capabilities(pad);
+    desc
+}
+
+/// Get the gamepad connected at this index.
+fn connected_pad(index: usize) -> Result<Gamepad, RumbleError> {
+    Gamepad::get_all().into_iter()
+        .nth(index)
+        .and_then(|pad| pad)
+        .ok_or(RumbleError::NotConnected)
+}
+
+/// Does this description still describe the gamepad, with the same layout?
+fn same_gamepad(desc: &GamepadDescription, pad: &Gamepad, axis_count: usize, button_count: usize) -> bool {
+    desc.mapping == mapping_type(pad.mapping())
+        && desc.axis_count == axis_count
+        && desc.button_count == button_count
+        && desc.name == pad.id()
+}
+
+/// Snapshot the current gamepad state.
+fn state(pad: &Gamepad) -> GamepadState {
+    GamepadState::new(
+        pad.timestamp(),
+        pad.axes(),
+        pad.buttons().iter()
+            .map(|b| (b.pressed(), b.value()))
+            .collect(),
+    )
+}
+
+/// Reads gamepads from the browser, using [stdweb](https://github.com/koute/stdweb).
+///
+/// Requires the `stdweb` feature (enabled by default).
+/// This is the default source of a [Monitor](struct.Monitor.html).
+///
+/// Focus is tracked through the window's `blur` and `focus` events, and the document's `visibilitychange` event.
+/// Connections are also reported by the window's `gamepadconnected` and `gamepaddisconnected` events,
+/// as they happen.
+/// The listeners are removed when the source is dropped.
+#[derive(Debug)]
+pub struct StdwebSource {
+    focus: Rc<FocusTracker>,
+    connection_changes: Rc<RefCell<Vec<ConnectionChange>>>,
+    /// Removes the focus listeners.
+    remove_focus_listeners: Value,
+    connection_listeners: Vec<EventListenerHandle>,
+    /// Button values, reused by each snapshot.
+    buttons: Vec<(bool, f64)>,
+}
+
+impl StdwebSource {
+
+    /// Create a source reading `navigator.getGamepads()`.
+    pub fn new() -> Self {
+        let focus = Rc::new(FocusTracker::new());
+
+        let tracker = focus.clone();
+        let set_focused = move |focused: bool| tracker.set_focused(focused);
+        let remove_focus_listeners = js! {
+            var set_focused = @{set_focused};
+            var blur = function() { set_focused(false); };
+            var update = function() {
+                set_focused(!document.hidden && document.hasFocus());
+            };
+            window.addEventListener("blur", blur);
+            window.addEventListener("focus", update);
+            document.addEventListener("visibilitychange", update);
+            return function() {
+                window.removeEventListener("blur", blur);
+                window.removeEventListener("focus", update);
+                document.removeEventListener("visibilitychange", update);
+                set_focused.drop();
+            };
+        };
+
+        let connection_changes = Rc::new(RefCell::new(vec![]));
+
+        let changes = connection_changes.clone();
+        let connected = web::window().add_event_listener(move |event: GamepadConnectedEvent| {
+            let pad = event.gamepad();
+            changes.borrow_mut().push(ConnectionChange::Connected(description(&pad), state(&pad)));
+        });
+        let changes = connection_changes.clone();
+        let disconnected = web::window().add_event_listener(move |event: GamepadDisconnectedEvent| {
+            let index = event.gamepad().index();
+            if index >= 0 {
+                changes.borrow_mut().push(ConnectionChange::Disconnected(index as usize));
+            }
+        });
+
+        StdwebSource {
+            focus,
+            connection_changes,
+            remove_focus_listeners,
+            connection_listeners: vec![connected, disconnected],
+            buttons: vec![],
+        }
+    }
+}
+
+impl Drop for StdwebSource {
+    fn drop(&mut self) {
+        js! { @(no_return)
+            @{&self.remove_focus_listeners}();
+        }
+        for listener in self.connection_listeners.drain(..) {
+            listener.remove();
+        }
+    }
+}
+
+impl Default for StdwebSource {
+    fn default() -> Self {
+        StdwebSource::new()
+    }
+}
+
+impl GamepadSource for StdwebSource {
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>> {
+        let mut pads = vec![];
+        self.snapshot_into(&mut pads);
+        pads
+    }
+
+    fn snapshot_into(&mut self, pads: &mut Vec<Option<(GamepadDescription, GamepadState)>>) {
+
+        // navigator.getGamepads() MUST be called each update.
+        // Chrome only updates Gamepad state in get_gamepads()
+        // (Counter to MDN documentation, which indicates we can save Gamepad references)
+        let raw_pads = Gamepad::get_all();
+        pads.resize_with(raw_pads.len(), || None);
+
+        for (raw, pad) in pads.iter_mut().zip(&raw_pads) {
+            let pad = match *pad {
+                Some(ref pad) => pad,
+                None => {
+                    *raw = None;
+                    continue;
+                },
+            };
+
+            // stdweb copies the axes and buttons into new Vecs
+            let axes = pad.axes();
+            let buttons = pad.buttons();
+            match *raw {
+                // the description is only read again when the gamepad or its layout changes
+                Some((ref desc, ref mut state)) if same_gamepad(desc, pad, axes.len(), buttons.len()) => {
+                    self.buttons.clear();
+                    self.buttons.extend(buttons.iter().map(|b| (b.pressed(), b.value())));
+                    state.set_values(pad.timestamp(), &axes, &self.buttons);
+                },
+                _ => *raw = Some((description(pad), state(pad))),
+            }
+        }
+    }
+
+    fn focus(&mut self) -> FocusState {
+        self.focus.check()
+    }
+
+    fn connection_changes(&mut self) -> Vec<ConnectionChange> {
+        mem::take(&mut *self.connection_changes.borrow_mut())
+    }
+
+    fn rumble(&mut self, index: usize, effect: &RumbleEffect) -> Result<(), RumbleError> {
+        let pad = connected_pad(index)?;
+        let method = rumble_capabilities(&pad).method(effect).ok_or(RumbleError::Unsupported)?;
+
+        match method.effect_type() {
+            Some(effect_type) => {
+                let params = vec![
+                    effect.start_delay,
+                    effect.duration,
+                    effect.strong_magnitude,
+                    effect.weak_magnitude,
+                    effect.left_trigger,
+                    effect.right_trigger,
+                ];
+                js! { @(no_return)
+                    var params = @{params};
+                    var result = @{&pad}.vibrationActuator.playEffect(@{effect_type}, {
+                        startDelay: params[0],
+                        duration: params[1],
+                        strongMagnitude: params[2],
+                        weakMagnitude: params[3],
+                        leftTrigger: params[4],
+                        rightTrigger: params[5]
+                    });
+                    // the promise is rejected if another effect replaces this one
+                    if (result && result.catch) {
+                        result.catch(function() {});
+                    }
+                }
+            },
+            None => js! { @(no_return)
+                @{&pad}.hapticActuators[0].pulse(@{effect.magnitude()}, @{effect.duration});
+            },
+        }
+        Ok(())
+    }
+
+    fn stop_rumble(&mut self, index: usize) -> Result<(), RumbleError> {
+        let pad = connected_pad(index)?;
+        if !rumble_capabilities(&pad).any() {
+            return Err(RumbleError::Unsupported);
+        }
+
+        js! { @(no_return)
+            var pad = @{&pad};
+            var actuator = pad.vibrationActuator;
+            if (actuator && actuator.reset) {
+                var result = actuator.reset();
+                if (result && result.catch) {
+                    result.catch(function() {});
+                }
+            }
+            else {
+                pad.hapticActuators[0].pulse(0, 0);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use std::f64::consts::{
+    FRAC_PI_2,
+    FRAC_PI_4,
+};
+
+/// A direction on a 4-way digital pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction4 {
+    /// Toward -y.
+    Up,
+    /// Toward +y.
+    Down,
+    /// Toward -x.
+    Left,
+    /// Toward +x.
+    Right,
+}
+
+/// A direction on an 8-way digital pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction8 {
+    /// Toward -y.
+    Up,
+    /// Toward +x, -y.
+    UpRight,
+    /// Toward +x.
+    Right,
+    /// Toward +x, +y.
+    DownRight,
+    /// Toward +y.
+    Down,
+    /// Toward -x, +y.
+    DownLeft,
+    /// Toward -x.
+    Left,
+    /// Toward -x, -y.
+    UpLeft,
+}
+
+/// The position of an analog stick, from a [MappedEvent::Stick](enum.MappedEvent.html#variant.Stick).
+///
+/// Like the axes, x goes from left (-1.0) to right (1.0), and y from up (-1.0) to down (1.0).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StickPosition {
+    /// The horizontal axis [-1–1].
+    pub x: f64,
+    /// The vertical axis [-1–1].
+    pub y: f64,
+}
+
+impl StickPosition {
+
+    /// Create a stick position.
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            x,
+            y,
+        }
+    }
+
+    /// The distance from center.
+    ///
+    /// This is 1.0 when pushed fully along one axis,
+    /// but may be greater on the diagonals, depending on the stick's gate.
+    pub fn magnitude(&self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    /// The angle in radians [-π–π], counterclockwise from right.
+    ///
+    /// Right is 0.0, up is π/2, and down is -π/2.
+    pub fn angle(&self) -> f64 {
+        // subtract rather than negate, so y = 0.0 gives 0.0 (not -0.0, which would make left -π)
+        (0.0 - self.y).atan2(self.x)
+    }
+
+    /// The nearest of 4 directions, or `None` if the magnitude is below `threshold`.
+    pub fn direction4(&self, threshold: f64) -> Option<Direction4> {
+        if self.magnitude() < threshold || self.magnitude() == 0.0 {
+            return None;
+        }
+
+        let sector = (self.angle() / FRAC_PI_2).round() as i32;
+        Some(match sector.rem_euclid(4) {
+            0 => Direction4::Right,
+            1 => Direction4::Up,
+            2 => Direction4::Left,
+            _ => Direction4::Down,
+        })
+    }
+
+    /// The nearest of 8 directions, or `None` if the magnitude is below `threshold`.
+    pub fn direction8(&self, threshold: f64) -> Option<Direction8> {
+        if self.magnitude() < threshold || self.magnitude() == 0.0 {
+            return None;
+        }
+
+        let sector = (self.angle() / FRAC_PI_4).round() as i32;
+        Some(match sector.rem_euclid(8) {
+            0 => Direction8::Right,
+            1 => Direction8::UpRight,
+            2 => Direction8::Up,
+            3 => Direction8::UpLeft,
+            4 => Direction8::Left,
+            5 => Direction8::DownLeft,
+            6 => Direction8::Down,
+            _ => Direction8::DownRight,
+        })
+    }
+}
+
+impl From<(f64, f64)> for StickPosition {
+    fn from((x, y): (f64, f64)) -> Self {
+        StickPosition::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_stick_polar() {
+        assert_eq!(StickPosition::new(0.0, 0.0).magnitude(), 0.0);
+        assert_eq!(StickPosition::new(0.6, -0.8).magnitude(), 1.0);
+
+        assert_eq!(StickPosition::new(1.0, 0.0).angle(), 0.0);
+        assert_eq!(StickPosition::new(0.0, -1.0).angle(), FRAC_PI_2);
+        assert_eq!(StickPosition::new(0.0, 1.0).angle(), -FRAC_PI_2);
+        assert_eq!(StickPosition::new(-1.0, 0.0).angle(), PI);
+    }
+
+    #[test]
+    fn test_stick_direction4() {
+        assert_eq!(StickPosition::new(0.0, 0.0).direction4(0.0), None);
+        assert_eq!(StickPosition::new(0.1, 0.0).direction4(0.5), None);
+        assert_eq!(StickPosition::new(0.9, 0.2).direction4(0.5), Some(Direction4::Right));
+        assert_eq!(StickPosition::new(0.2, -0.9).direction4(0.5), Some(Direction4::Up));
+        assert_eq!(StickPosition::new(-0.9, -0.2).direction4(0.5), Some(Direction4::Left));
+        assert_eq!(StickPosition::new(-0.2, 0.9).direction4(0.5), Some(Direction4::Down));
+    }
+
+    #[test]
+    fn test_stick_direction8() {
+        assert_eq!(StickPosition::new(0.0, 0.0).direction8(0.0), None);
+        assert_eq!(StickPosition::new(0.9, 0.1).direction8(0.5), Some(Direction8::Right));
+        assert_eq!(StickPosition::new(0.7, -0.7).direction8(0.5), Some(Direction8::UpRight));
+        assert_eq!(StickPosition::new(0.1, -0.9).direction8(0.5), Some(Direction8::Up));
+        assert_eq!(StickPosition::new(-0.7, -0.7).direction8(0.5), Some(Direction8::UpLeft));
+        assert_eq!(StickPosition::new(-0.9, 0.1).direction8(0.5), Some(Direction8::Left));
+        assert_eq!(StickPosition::new(-0.7, 0.7).direction8(0.5), Some(Direction8::DownLeft));
+        assert_eq!(StickPosition::new(0.0, 1.0).direction8(0.5), Some(Direction8::Down));
+        assert_eq!(StickPosition::new(0.7, 0.7).direction8(0.5), Some(Direction8::DownRight));
+    }
+}
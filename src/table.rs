@@ -0,0 +1,360 @@
+use ::event::{
+    EventData,
+    MappedEvent,
+};
+use ::mapping::{
+    Axis,
+    Button,
+    Mapping,
+    STANDARD_AXES,
+    STANDARD_BUTTONS,
+};
+use ::sdl::{
+    axis_amount,
+    AxisHalf,
+    SdlBinding,
+    SdlInput,
+    SdlMapping,
+    SdlTarget,
+};
+use ::trigger::Trigger;
+
+/// The direction bits of each position of a hat switch, clockwise from up.
+const HAT_DIRECTIONS: [u8; 8] = [1, 3, 2, 6, 4, 12, 8, 9];
+
+/// The direction bits (1 up, 2 right, 4 down, 8 left) of a hat switch read from an axis.
+///
+/// Browsers report a hat as one axis, from -1.0 (up) clockwise in steps of 2/7, to 1.0 (up-left).
+/// At rest, it reads past 1.0.
+pub(crate) fn hat_mask(value: f64) -> u8 {
+    let position = ((value + 1.0) * 3.5).round();
+    if (0.0..8.0).contains(&position) {
+        HAT_DIRECTIONS[position as usize]
+    } else {
+        0
+    }
+}
+
+/// The sign of a half axis, where a full axis is positive.
+fn half_sign(half: Option<AxisHalf>) -> f64 {
+    match half {
+        Some(AxisHalf::Negative) => -1.0,
+        _ => 1.0,
+    }
+}
+
+/// Map a raw input event through `bindings`, passing each resulting event to `emit`.
+///
+/// One raw input may drive several standard inputs, such as a hat switch,
+/// or an axis split into two buttons.
+/// Buttons read from axes and hats report their value, and are pressed while past halfway.
+/// `previous` is the axis' value before this event, if known:
+/// then only the standard inputs which changed are emitted.
+/// Without it, each standard input the axis drives is emitted,
+/// so these buttons may be released (or pressed) more than once in a row.
+/// [Triggers](enum.Trigger.html) only report their value;
+/// the [Monitor](struct.Monitor.html#method.set_trigger_threshold) presses them.
+pub(crate) fn map_bindings<'a, I: IntoIterator<Item = &'a SdlBinding>, F: FnMut(MappedEvent)>(bindings: I, data: &EventData, previous: Option<f64>, mut emit: F) {
+    for binding in bindings {
+        match (binding.input, *data) {
+            (SdlInput::Button(i), EventData::Button(index, pressed)) if i == index =>
+                if let SdlTarget::Button(button) = binding.target {
+                    emit(if pressed { MappedEvent::ButtonPress(button) } else { MappedEvent::ButtonRelease(button) });
+                },
+            (SdlInput::Button(i), EventData::ButtonValue(index, value)) if i == index =>
+                emit(match binding.target {
+                    SdlTarget::Button(button) => MappedEvent::ButtonValue(button, value),
+                    SdlTarget::Axis(axis, half) => MappedEvent::Axis(axis, half_sign(half) * value),
+                }),
+            (SdlInput::Axis { index: i, half: input_half, inverted }, EventData::Axis(index, value)) if i == index => {
+                let read = |value| {
+                    // the part of the input's range in use, as [0–1]
+                    let amount = axis_amount(value, input_half, inverted);
+                    match binding.target {
+                        SdlTarget::Button(_) => amount,
+                        SdlTarget::Axis(_, None) if input_half.is_none() => if inverted { 0.0 - value } else { value },
+                        SdlTarget::Axis(_, None) => amount * 2.0 - 1.0,
+                        SdlTarget::Axis(_, half) => half_sign(half) * amount,
+                    }
+                };
+                emit_read(binding.target, read(value), previous.map(read), &mut emit);
+            },
+            (SdlInput::Hat(i, mask), EventData::Axis(index, value)) if i == index => {
+                let read = |value| {
+                    let amount = if hat_mask(value) & mask != 0 { 1.0 } else { 0.0 };
+                    match binding.target {
+                        SdlTarget::Button(_) => amount,
+                        SdlTarget::Axis(_, half) => half_sign(half) * amount,
+                    }
+                };
+                emit_read(binding.target, read(value), previous.map(read), &mut emit);
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Emit a standard input read from an axis or hat, unless it is unchanged from `previous`.
+///
+/// Buttons are pressed past halfway, unless they are triggers, which only report their value.
+fn emit_read<F: FnMut(MappedEvent)>(target: SdlTarget, value: f64, previous: Option<f64>, emit: &mut F) {
+    let changed = previous.map_or(true, |previous| previous.to_bits() != value.to_bits());
+    match target {
+        SdlTarget::Button(button) => {
+            let pressed = value >= 0.5;
+            if Trigger::from_button(button).is_none() && previous.map_or(true, |previous| (previous >= 0.5) != pressed) {
+                emit(if pressed { MappedEvent::ButtonPress(button) } else { MappedEvent::ButtonRelease(button) });
+            }
+            if changed {
+                emit(MappedEvent::ButtonValue(button, value));
+            }
+        },
+        SdlTarget::Axis(axis, _) =>
+            if changed {
+                emit(MappedEvent::Axis(axis, value));
+            },
+    }
+}
+
+/// A [Mapping](trait.Mapping.html) defined by data, to use in place of the browser's mapping.
+///
+/// Each standard button or axis is bound to one raw input, in the same terms as an
+/// [SdlMapping](struct.SdlMapping.html):
+/// a button, an axis (optionally inverted, or half of its range), or a hat switch direction.
+/// Standard axes may be bound to half their range (`+leftx`), so two buttons can drive one axis.
+///
+/// Hat switches are read from axes, as browsers report them,
+/// so a [Hat](enum.SdlInput.html#variant.Hat) input holds an axis index.
+/// Mappings built by a [MappingWizard](struct.MappingWizard.html) can be used directly.
+///
+/// Give gamepads a table through
+/// [Monitor::set_mapping_resolver()](struct.Monitor.html#method.set_mapping_resolver),
+/// and their events are [mapped](struct.Event.html#method.map) through it.
+///
+/// ```
+/// use gamepad_web::*;
+///
+/// let mut table = MappingTable::new();
+/// table.bind(SdlTarget::Button(Button::South), SdlInput::Button(2));
+/// table.bind(SdlTarget::Axis(Axis::LeftStickY, None), SdlInput::Axis { index: 1, half: None, inverted: true });
+/// table.bind(SdlTarget::Button(Button::RT2), SdlInput::Axis { index: 5, half: Some(AxisHalf::Positive), inverted: false });
+/// table.bind_hat(6);
+///
+/// assert_eq!(table.map_button(2), Some(Button::South));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MappingTable {
+    bindings: Vec<SdlBinding>,
+}
+
+impl MappingTable {
+
+    /// Create an empty table, which maps nothing.
+    pub fn new() -> Self {
+        Self {
+            bindings: vec![],
+        }
+    }
+
+    /// Create a table of the [standard gamepad](https://www.w3.org/TR/gamepad/#remapping) layout,
+    /// to start from when only a few inputs differ.
+    pub fn standard() -> Self {
+        let buttons = STANDARD_BUTTONS.iter()
+            .enumerate()
+            .map(|(index, &button)| SdlBinding { target: SdlTarget::Button(button), input: SdlInput::Button(index) });
+        let axes = STANDARD_AXES.iter()
+            .enumerate()
+            .map(|(index, &axis)| SdlBinding {
+                target: SdlTarget::Axis(axis, None),
+                input: SdlInput::Axis { index, half: None, inverted: false },
+            });
+
+        Self {
+            bindings: buttons.chain(axes).collect(),
+        }
+    }
+
+    /// Bind a standard button or axis to a raw input, replacing its previous binding.
+    pub fn bind(&mut self, target: SdlTarget, input: SdlInput) {
+        self.unbind(target);
+        self.bindings.push(SdlBinding { target, input });
+    }
+
+    /// Bind the directional pad to a hat switch, read from the axis at this index.
+    pub fn bind_hat(&mut self, index: usize) {
+        self.bind(SdlTarget::Button(Button::Up), SdlInput::Hat(index, 1));
+        self.bind(SdlTarget::Button(Button::Right), SdlInput::Hat(index, 2));
+        self.bind(SdlTarget::Button(Button::Down), SdlInput::Hat(index, 4));
+        self.bind(SdlTarget::Button(Button::Left), SdlInput::Hat(index, 8));
+    }
+
+    /// Remove the binding of a standard button or axis.
+    pub fn unbind(&mut self, target: SdlTarget) {
+        self.bindings.retain(|binding| binding.target != target);
+    }
+
+    /// The binding of each standard button and axis.
+    pub fn bindings(&self) -> &[SdlBinding] {
+        &self.bindings
+    }
+}
+
+impl From<SdlMapping> for MappingTable {
+    fn from(mapping: SdlMapping) -> Self {
+        Self {
+            bindings: mapping.bindings,
+        }
+    }
+}
+
+/// Relates buttons bound to buttons, and axes bound to full, non-inverted axes.
+/// Other bindings are only used when [mapping events](struct.Event.html#method.map).
+impl Mapping for MappingTable {
+    fn map_button(&self, index: usize) -> Option<Button> {
+        self.bindings.iter()
+            .filter(|binding| binding.input == SdlInput::Button(index))
+            .filter_map(|binding| match binding.target {
+                SdlTarget::Button(button) => Some(button),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn button_index(&self, button: Button) -> Option<usize> {
+        self.bindings.iter()
+            .filter(|binding| binding.target == SdlTarget::Button(button))
+            .filter_map(|binding| match binding.input {
+                SdlInput::Button(index) => Some(index),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn map_axis(&self, index: usize) -> Option<Axis> {
+        let input = SdlInput::Axis { index, half: None, inverted: false };
+        self.bindings.iter()
+            .filter(|binding| binding.input == input)
+            .filter_map(|binding| match binding.target {
+                SdlTarget::Axis(axis, None) => Some(axis),
+                _ => None,
+            })
+            .next()
+    }
+
+    fn axis_index(&self, axis: Axis) -> Option<usize> {
+        self.bindings.iter()
+            .filter(|binding| binding.target == SdlTarget::Axis(axis, None))
+            .filter_map(|binding| match binding.input {
+                SdlInput::Axis { index, half: None, inverted: false } => Some(index),
+                _ => None,
+            })
+            .next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::GamepadMappingType;
+
+    fn map(table: &MappingTable, data: EventData) -> Vec<MappedEvent> {
+        map_since(table, data, None)
+    }
+
+    fn map_since(table: &MappingTable, data: EventData, previous: Option<f64>) -> Vec<MappedEvent> {
+        let mut events = vec![];
+        map_bindings(table.bindings(), &data, previous, |event| events.push(event));
+        events
+    }
+
+    #[test]
+    fn test_hat_mask() {
+        assert_eq!(hat_mask(-1.0), 1);
+        assert_eq!(hat_mask(-5.0 / 7.0), 3);
+        assert_eq!(hat_mask(3.0 / 7.0), 12);
+        assert_eq!(hat_mask(1.0), 9);
+        assert_eq!(hat_mask(9.0 / 7.0), 0);
+        assert_eq!(hat_mask(-1.5), 0);
+    }
+
+    #[test]
+    fn test_mapping_table_standard() {
+        let table = MappingTable::standard();
+        let standard = GamepadMappingType::Standard;
+        for index in 0..20 {
+            assert_eq!(table.map_button(index), standard.map_button(index));
+            assert_eq!(table.map_axis(index), standard.map_axis(index));
+        }
+        assert_eq!(table.button_index(Button::Home), Some(16));
+        assert_eq!(table.axis_index(Axis::RightStickY), Some(3));
+    }
+
+    #[test]
+    fn test_mapping_table_events() {
+        let mut table = MappingTable::new();
+        table.bind(SdlTarget::Button(Button::South), SdlInput::Button(3));
+        table.bind(SdlTarget::Axis(Axis::LeftStickY, None), SdlInput::Axis { index: 1, half: None, inverted: true });
+        table.bind(SdlTarget::Button(Button::LT2), SdlInput::Axis { index: 2, half: None, inverted: false });
+        table.bind(SdlTarget::Button(Button::LT1), SdlInput::Axis { index: 4, half: Some(AxisHalf::Negative), inverted: false });
+        table.bind(SdlTarget::Button(Button::RT1), SdlInput::Axis { index: 4, half: Some(AxisHalf::Positive), inverted: false });
+        table.bind(SdlTarget::Axis(Axis::RightStickX, Some(AxisHalf::Negative)), SdlInput::Button(0));
+        table.bind(SdlTarget::Axis(Axis::RightStickX, Some(AxisHalf::Positive)), SdlInput::Button(1));
+        table.bind_hat(5);
+        assert_eq!(table.map_button(3), Some(Button::South));
+        assert_eq!(table.map_axis(1), None);
+
+        // buttons, and buttons driving half axes
+        assert_eq!(map(&table, EventData::Button(3, true)), vec![MappedEvent::ButtonPress(Button::South)]);
+        assert_eq!(map(&table, EventData::ButtonValue(3, 1.0)), vec![MappedEvent::ButtonValue(Button::South, 1.0)]);
+        assert_eq!(map(&table, EventData::Button(0, true)), vec![]);
+        assert_eq!(map(&table, EventData::ButtonValue(0, 1.0)), vec![MappedEvent::Axis(Axis::RightStickX, -1.0)]);
+        assert_eq!(map(&table, EventData::ButtonValue(1, 0.5)), vec![MappedEvent::Axis(Axis::RightStickX, 0.5)]);
+
+        // inverted axes, and a trigger resting at -1.0
+        assert_eq!(map(&table, EventData::Axis(1, 0.25)), vec![MappedEvent::Axis(Axis::LeftStickY, -0.25)]);
+        assert_eq!(map(&table, EventData::Axis(2, 0.5)), vec![MappedEvent::ButtonValue(Button::LT2, 0.75)]);
+        assert_eq!(map(&table, EventData::Axis(2, -1.0)), vec![MappedEvent::ButtonValue(Button::LT2, 0.0)]);
+
+        // an axis split into two buttons
+        assert_eq!(map(&table, EventData::Axis(4, -1.0)), vec![
+            MappedEvent::ButtonPress(Button::LT1),
+            MappedEvent::ButtonValue(Button::LT1, 1.0),
+            MappedEvent::ButtonRelease(Button::RT1),
+            MappedEvent::ButtonValue(Button::RT1, 0.0),
+        ]);
+
+        // a hat switch, with diagonals pressing two buttons
+        assert_eq!(map(&table, EventData::Axis(5, -5.0 / 7.0)), vec![
+            MappedEvent::ButtonPress(Button::Up),
+            MappedEvent::ButtonValue(Button::Up, 1.0),
+            MappedEvent::ButtonPress(Button::Right),
+            MappedEvent::ButtonValue(Button::Right, 1.0),
+            MappedEvent::ButtonRelease(Button::Down),
+            MappedEvent::ButtonValue(Button::Down, 0.0),
+            MappedEvent::ButtonRelease(Button::Left),
+            MappedEvent::ButtonValue(Button::Left, 0.0),
+        ]);
+        assert_eq!(map(&table, EventData::Axis(6, 1.0)), vec![]);
+
+        // with the previous value, only changes are emitted
+        assert_eq!(map_since(&table, EventData::Axis(5, -1.0), Some(-5.0 / 7.0)), vec![
+            MappedEvent::ButtonRelease(Button::Right),
+            MappedEvent::ButtonValue(Button::Right, 0.0),
+        ]);
+        assert_eq!(map_since(&table, EventData::Axis(4, 0.75), Some(0.25)), vec![
+            MappedEvent::ButtonPress(Button::RT1),
+            MappedEvent::ButtonValue(Button::RT1, 0.75),
+        ]);
+        assert_eq!(map_since(&table, EventData::Axis(4, 0.5), Some(0.75)), vec![MappedEvent::ButtonValue(Button::RT1, 0.5)]);
+        assert_eq!(map_since(&table, EventData::Axis(2, 0.5), Some(0.0)), vec![MappedEvent::ButtonValue(Button::LT2, 0.75)]);
+        assert_eq!(map_since(&table, EventData::Axis(1, 0.25), Some(0.0)), vec![MappedEvent::Axis(Axis::LeftStickY, -0.25)]);
+
+        // rebinding replaces
+        table.bind(SdlTarget::Button(Button::South), SdlInput::Button(4));
+        assert_eq!(table.map_button(3), None);
+        assert_eq!(table.button_index(Button::South), Some(4));
+        table.unbind(SdlTarget::Button(Button::South));
+        assert_eq!(map(&table, EventData::Button(4, true)), vec![]);
+    }
+}
@@ -0,0 +1,163 @@
+use ::gamepad::GamepadState;
+use ::mapping::{
+    Axis,
+    Button,
+};
+use ::sdl::{
+    axis_amount,
+    SdlInput,
+};
+use ::table::hat_mask;
+
+/// An analog trigger on the standard gamepad.
+///
+/// Triggers are reported as buttons by the standard mapping, with values [0–1],
+/// and often as axes [-1–1] resting at -1.0 by others.
+/// The [Monitor](struct.Monitor.html) reads both as the trigger's [axis()](#method.axis), [0–1].
+/// When read from an axis, it presses its [button()](#method.button) past the
+/// [trigger threshold](struct.Monitor.html#method.set_trigger_threshold).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Trigger {
+    /// The left trigger: [LeftTrigger](enum.Axis.html#variant.LeftTrigger) and [LT2](enum.Button.html#variant.LT2).
+    Left,
+    /// The right trigger: [RightTrigger](enum.Axis.html#variant.RightTrigger) and [RT2](enum.Button.html#variant.RT2).
+    Right,
+}
+
+impl Trigger {
+
+    /// The axis of this trigger.
+    pub fn axis(self) -> Axis {
+        match self {
+            Trigger::Left => Axis::LeftTrigger,
+            Trigger::Right => Axis::RightTrigger,
+        }
+    }
+
+    /// The button of this trigger.
+    pub fn button(self) -> Button {
+        match self {
+            Trigger::Left => Button::LT2,
+            Trigger::Right => Button::RT2,
+        }
+    }
+
+    /// The trigger with this button, if any.
+    pub fn from_button(button: Button) -> Option<Trigger> {
+        match button {
+            Button::LT2 => Some(Trigger::Left),
+            Button::RT2 => Some(Trigger::Right),
+            _ => None,
+        }
+    }
+}
+
+/// The values at which a trigger is pressed and released, kept apart so a trigger held near one does not flicker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TriggerThreshold {
+    pub(crate) press: f64,
+    pub(crate) release: f64,
+}
+
+impl Default for TriggerThreshold {
+    fn default() -> Self {
+        Self {
+            press: 0.5,
+            release: 0.4,
+        }
+    }
+}
+
+/// The reading of a trigger, kept between updates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct TriggerState {
+    pub(crate) value: f64,
+    pub(crate) pressed: bool,
+    /// Has its axis read anything but 0.0?
+    /// Firefox reports some trigger axes as 0.0 until first touched, though they rest at -1.0.
+    touched: bool,
+}
+
+impl TriggerState {
+
+    /// Read the trigger from its raw `input` in `state`, following this previous reading.
+    pub(crate) fn read(self, input: SdlInput, state: &GamepadState, threshold: TriggerThreshold) -> Self {
+        let mut touched = self.touched;
+        let value = match input {
+            SdlInput::Button(index) => state.get_button_value(index).unwrap_or(0.0),
+            SdlInput::Axis { index, half, inverted } => match state.get_axis(index) {
+                Some(value) if half.is_none() && !touched && value == 0.0 => 0.0,
+                Some(value) => {
+                    touched = true;
+                    axis_amount(value, half, inverted)
+                },
+                None => 0.0,
+            },
+            SdlInput::Hat(index, mask) => match state.get_axis(index) {
+                Some(value) if hat_mask(value) & mask != 0 => 1.0,
+                _ => 0.0,
+            },
+        };
+        let pressed = if self.pressed { value > threshold.release } else { value >= threshold.press };
+
+        Self {
+            value,
+            pressed,
+            touched,
+        }
+    }
+
+    /// Forget that its axis was touched, so 0.0 reads as released, as when its gamepad is released.
+    pub(crate) fn untouched(self) -> Self {
+        Self {
+            touched: false,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::sdl::AxisHalf;
+
+    fn read(previous: TriggerState, input: SdlInput, axes: Vec<f64>, buttons: Vec<(bool, f64)>) -> TriggerState {
+        previous.read(input, &GamepadState::new(0.0, axes, buttons), TriggerThreshold::default())
+    }
+
+    #[test]
+    fn test_trigger_read() {
+        let button = SdlInput::Button(1);
+        let axis = SdlInput::Axis { index: 0, half: None, inverted: false };
+        let half = SdlInput::Axis { index: 0, half: Some(AxisHalf::Negative), inverted: false };
+
+        // pressed at 0.5, and released below 0.4
+        let state = read(TriggerState::default(), button, vec![], vec![(false, 0.0), (true, 0.45)]);
+        assert_eq!((state.value, state.pressed), (0.45, false));
+        let state = read(state, button, vec![], vec![(false, 0.0), (true, 0.5)]);
+        assert_eq!((state.value, state.pressed), (0.5, true));
+        let state = read(state, button, vec![], vec![(false, 0.0), (true, 0.45)]);
+        assert_eq!((state.value, state.pressed), (0.45, true));
+        let state = read(state, button, vec![], vec![(false, 0.0), (false, 0.25)]);
+        assert_eq!((state.value, state.pressed), (0.25, false));
+
+        // an axis reading 0.0 until first touched
+        let state = read(TriggerState::default(), axis, vec![0.0], vec![]);
+        assert_eq!((state.value, state.pressed), (0.0, false));
+        let state = read(state, axis, vec![-0.5], vec![]);
+        assert_eq!(state.value, 0.25);
+        let state = read(state, axis, vec![0.0], vec![]);
+        assert_eq!((state.value, state.pressed), (0.5, true));
+        let state = read(state, axis, vec![-1.0], vec![]);
+        assert_eq!((state.value, state.pressed), (0.0, false));
+
+        // half axes rest at 0.0
+        let state = read(TriggerState::default(), half, vec![-0.75], vec![]);
+        assert_eq!((state.value, state.pressed), (0.75, true));
+
+        // missing inputs read as released
+        let state = read(state, half, vec![], vec![]);
+        assert_eq!((state.value, state.pressed), (0.0, false));
+    }
+}
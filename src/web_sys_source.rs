@@ -0,0 +1,342 @@
+use ::gamepad::{
+    GamepadDescription,
+    GamepadMappingType,
+    GamepadState,
+};
+use ::rumble::{
+    RumbleCapabilities,
+    RumbleEffect,
+    RumbleError,
+};
+use ::source::{
+    ConnectionChange,
+    FocusState,
+    FocusTracker,
+    GamepadSource,
+};
+
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+use js_sys::{
+    Array,
+    Function,
+    Object,
+    Reflect,
+};
+use wasm_bindgen::{
+    JsCast,
+    JsValue,
+};
+use wasm_bindgen::closure::Closure;
+use web_sys::{
+    self,
+    Gamepad,
+    GamepadButton,
+    GamepadEvent,
+};
+
+fn mapping_type(mapping: web_sys::GamepadMappingType) -> GamepadMappingType {
+    match mapping {
+        web_sys::GamepadMappingType::Standard => GamepadMappingType::Standard,
+        _ => GamepadMappingType::NoMapping,
+    }
+}
+
+/// Get a property, or `undefined`.
+///
+/// The actuator APIs are missing from some browsers, and unstable in web-sys.
+fn property(target: &JsValue, key: &str) -> JsValue {
+    Reflect::get(target, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+}
+
+/// Call a method, ignoring any returned promise, which is rejected if another effect replaces this one.
+fn call(target: &JsValue, method: &str, args: &Array) -> Result<(), RumbleError> {
+    let function = property(target, method).dyn_into::<Function>().map_err(|_| RumbleError::Unsupported)?;
+    let result = function.apply(target, args).map_err(|_| RumbleError::Unsupported)?;
+    if let Ok(catch) = property(&result, "catch").dyn_into::<Function>() {
+        let _ = catch.call1(&result, &Function::new_no_args(""));
+    }
+    Ok(())
+}
+
+/// Read the effects supported by the gamepad's actuators.
+fn rumble_capabilities(pad: &Gamepad) -> RumbleCapabilities {
+    let actuator = property(pad, "vibrationActuator");
+    // older browsers report a single effect type
+    let effects: Vec<String> = match property(&actuator, "effects").dyn_into::<Array>() {
+        Ok(effects) => effects.iter().filter_map(|effect| effect.as_string()).collect(),
+        Err(_) => property(&actuator, "type").as_string().into_iter().collect(),
+    };
+    let has_effect = |name: &str| effects.iter().any(|effect| effect == name);
+
+    RumbleCapabilities {
+        dual_rumble: has_effect("dual-rumble"),
+        trigger_rumble: has_effect("trigger-rumble"),
+        pulse: property(pad, "hapticActuators").dyn_into::<Array>().ok().map_or(false, |haptics| haptics.length() > 0),
+    }
+}
+
+fn description(pad: &Gamepad) -> GamepadDescription {
+    let mut desc = GamepadDescription::new(
+        pad.index() as i32,
+        pad.id(),
+        mapping_type(pad.mapping()),
+        pad.axes().length() as usize,
+        pad.buttons().length() as usize,
+    );
+    desc.rumble = rumble_capabilities(pad);
+    desc
+}
+
+/// Get the gamepad connected at this index.
+fn connected_pad(index: usize) -> Result<Gamepad, RumbleError> {
+    web_sys::window()
+        .and_then(|window| window.navigator().get_gamepads().ok())
+        .and_then(|raw_pads| raw_pads.get(index as u32).dyn_into::<Gamepad>().ok())
+        .filter(|pad| pad.connected())
+        .ok_or(RumbleError::NotConnected)
+}
+
+/// Does this description still describe the gamepad, with the same layout?
+fn same_gamepad(desc: &GamepadDescription, pad: &Gamepad, axis_count: usize, button_count: usize) -> bool {
+    desc.mapping == mapping_type(pad.mapping())
+        && desc.axis_count == axis_count
+        && desc.button_count == button_count
+        && desc.name == pad.id()
+}
+
+/// Snapshot the current gamepad state.
+fn state(pad: &Gamepad) -> GamepadState {
+    GamepadState::new(
+        pad.timestamp(),
+        pad.axes().iter()
+            .map(|axis| axis.as_f64().unwrap_or(0.0))
+            .collect(),
+        pad.buttons().iter()
+            .map(|b| b.unchecked_into::<GamepadButton>())
+            .map(|b| (b.pressed(), b.value()))
+            .collect(),
+    )
+}
+
+/// Is the document visible and focused?
+fn document_focused() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .map_or(true, |document| !document.hidden() && document.has_focus().unwrap_or(true))
+}
+
+/// The page's event listeners, kept until their source is dropped.
+#[derive(Debug)]
+struct Listeners {
+    window: web_sys::Window,
+    blur: Closure<dyn FnMut()>,
+    update: Closure<dyn FnMut()>,
+    connected: Closure<dyn FnMut(GamepadEvent)>,
+    disconnected: Closure<dyn FnMut(GamepadEvent)>,
+}
+
+impl Listeners {
+
+    /// Listen for focus and connection changes.
+    fn add(window: web_sys::Window, focus: &Rc<FocusTracker>, connection_changes: &Rc<RefCell<Vec<ConnectionChange>>>) -> Self {
+        let tracker = focus.clone();
+        let blur = Closure::wrap(Box::new(move || tracker.set_focused(false)) as Box<dyn FnMut()>);
+        let tracker = focus.clone();
+        let update = Closure::wrap(Box::new(move || tracker.set_focused(document_focused())) as Box<dyn FnMut()>);
+
+        let changes = connection_changes.clone();
+        let connected = Closure::wrap(Box::new(move |event: GamepadEvent| {
+            if let Some(pad) = event.gamepad() {
+                changes.borrow_mut().push(ConnectionChange::Connected(description(&pad), state(&pad)));
+            }
+        }) as Box<dyn FnMut(GamepadEvent)>);
+        let changes = connection_changes.clone();
+        let disconnected = Closure::wrap(Box::new(move |event: GamepadEvent| {
+            if let Some(pad) = event.gamepad() {
+                changes.borrow_mut().push(ConnectionChange::Disconnected(pad.index() as usize));
+            }
+        }) as Box<dyn FnMut(GamepadEvent)>);
+
+        let _ = window.add_event_listener_with_callback("blur", blur.as_ref().unchecked_ref());
+        let _ = window.add_event_listener_with_callback("focus", update.as_ref().unchecked_ref());
+        if let Some(document) = window.document() {
+            let _ = document.add_event_listener_with_callback("visibilitychange", update.as_ref().unchecked_ref());
+        }
+        let _ = window.add_event_listener_with_callback("gamepadconnected", connected.as_ref().unchecked_ref());
+        let _ = window.add_event_listener_with_callback("gamepaddisconnected", disconnected.as_ref().unchecked_ref());
+
+        Self {
+            window,
+            blur,
+            update,
+            connected,
+            disconnected,
+        }
+    }
+
+    /// Stop listening.
+    fn remove(self) {
+        let window = &self.window;
+        let _ = window.remove_event_listener_with_callback("blur", self.blur.as_ref().unchecked_ref());
+        let _ = window.remove_event_listener_with_callback("focus", self.update.as_ref().unchecked_ref());
+        if let Some(document) = window.document() {
+            let _ = document.remove_event_listener_with_callback("visibilitychange", self.update.as_ref().unchecked_ref());
+        }
+        let _ = window.remove_event_listener_with_callback("gamepadconnected", self.connected.as_ref().unchecked_ref());
+        let _ = window.remove_event_listener_with_callback("gamepaddisconnected", self.disconnected.as_ref().unchecked_ref());
+    }
+}
+
+/// Reads gamepads from the browser, using [web-sys](https://crates.io/crates/web-sys).
+///
+/// Requires the `web_sys` feature.
+/// This is the default source of a [Monitor](struct.Monitor.html) when the `stdweb` feature is disabled.
+///
+/// Focus is tracked through the window's `blur` and `focus` events, and the document's `visibilitychange` event.
+/// Connections are also reported by the window's `gamepadconnected` and `gamepaddisconnected` events,
+/// as they happen.
+/// The listeners are removed when the source is dropped.
+#[derive(Debug)]
+pub struct WebSysSource {
+    focus: Rc<FocusTracker>,
+    connection_changes: Rc<RefCell<Vec<ConnectionChange>>>,
+    listeners: Option<Listeners>,
+    /// Axis and button values, reused by each snapshot.
+    axes: Vec<f64>,
+    buttons: Vec<(bool, f64)>,
+}
+
+impl WebSysSource {
+
+    /// Create a source reading `navigator.getGamepads()`.
+    pub fn new() -> Self {
+        let focus = Rc::new(FocusTracker::new());
+        let connection_changes = Rc::new(RefCell::new(vec![]));
+        let listeners = web_sys::window().map(|window| Listeners::add(window, &focus, &connection_changes));
+
+        WebSysSource {
+            focus,
+            connection_changes,
+            listeners,
+            axes: vec![],
+            buttons: vec![],
+        }
+    }
+}
+
+impl Drop for WebSysSource {
+    fn drop(&mut self) {
+        if let Some(listeners) = self.listeners.take() {
+            listeners.remove();
+        }
+    }
+}
+
+impl Default for WebSysSource {
+    fn default() -> Self {
+        WebSysSource::new()
+    }
+}
+
+impl GamepadSource for WebSysSource {
+    fn snapshot(&mut self) -> Vec<Option<(GamepadDescription, GamepadState)>> {
+        let mut pads = vec![];
+        self.snapshot_into(&mut pads);
+        pads
+    }
+
+    fn snapshot_into(&mut self, pads: &mut Vec<Option<(GamepadDescription, GamepadState)>>) {
+
+        // navigator.getGamepads() MUST be called each update,
+        // see StdwebSource
+        let raw_pads = match web_sys::window().and_then(|window| window.navigator().get_gamepads().ok()) {
+            Some(raw_pads) => raw_pads,
+            None => {
+                pads.clear();
+                return;
+            },
+        };
+        pads.resize_with(raw_pads.length() as usize, || None);
+
+        for (i, raw) in pads.iter_mut().enumerate() {
+            // empty slots may be null, or a disconnected Gamepad
+            let pad = match raw_pads.get(i as u32).dyn_into::<Gamepad>().ok().filter(|pad| pad.connected()) {
+                Some(pad) => pad,
+                None => {
+                    *raw = None;
+                    continue;
+                },
+            };
+
+            let axes = pad.axes();
+            let buttons = pad.buttons();
+            match *raw {
+                // the description is only read again when the gamepad or its layout changes
+                Some((ref desc, ref mut state)) if same_gamepad(desc, &pad, axes.length() as usize, buttons.length() as usize) => {
+                    self.axes.clear();
+                    self.axes.extend(axes.iter().map(|axis| axis.as_f64().unwrap_or(0.0)));
+                    self.buttons.clear();
+                    self.buttons.extend(buttons.iter()
+                        .map(|b| b.unchecked_into::<GamepadButton>())
+                        .map(|b| (b.pressed(), b.value())));
+                    state.set_values(pad.timestamp(), &self.axes, &self.buttons);
+                },
+                _ => *raw = Some((description(&pad), state(&pad))),
+            }
+        }
+    }
+
+    fn focus(&mut self) -> FocusState {
+        self.focus.check()
+    }
+
+    fn connection_changes(&mut self) -> Vec<ConnectionChange> {
+        mem::take(&mut *self.connection_changes.borrow_mut())
+    }
+
+    fn rumble(&mut self, index: usize, effect: &RumbleEffect) -> Result<(), RumbleError> {
+        let pad = connected_pad(index)?;
+        let method = rumble_capabilities(&pad).method(effect).ok_or(RumbleError::Unsupported)?;
+
+        match method.effect_type() {
+            Some(effect_type) => {
+                let params = Object::new();
+                for &(key, value) in [
+                    ("startDelay", effect.start_delay),
+                    ("duration", effect.duration),
+                    ("strongMagnitude", effect.strong_magnitude),
+                    ("weakMagnitude", effect.weak_magnitude),
+                    ("leftTrigger", effect.left_trigger),
+                    ("rightTrigger", effect.right_trigger),
+                ].iter() {
+                    let _ = Reflect::set(&params, &JsValue::from_str(key), &JsValue::from_f64(value));
+                }
+                let args = Array::of2(&JsValue::from_str(effect_type), &params);
+                call(&property(&pad, "vibrationActuator"), "playEffect", &args)
+            },
+            None => {
+                let haptic = Array::from(&property(&pad, "hapticActuators")).get(0);
+                let args = Array::of2(&JsValue::from_f64(effect.magnitude()), &JsValue::from_f64(effect.duration));
+                call(&haptic, "pulse", &args)
+            },
+        }
+    }
+
+    fn stop_rumble(&mut self, index: usize) -> Result<(), RumbleError> {
+        let pad = connected_pad(index)?;
+        let capabilities = rumble_capabilities(&pad);
+        if capabilities.dual_rumble || capabilities.trigger_rumble {
+            call(&property(&pad, "vibrationActuator"), "reset", &Array::new())
+        }
+        else if capabilities.pulse {
+            let haptic = Array::from(&property(&pad, "hapticActuators")).get(0);
+            call(&haptic, "pulse", &Array::of2(&JsValue::from_f64(0.0), &JsValue::from_f64(0.0)))
+        }
+        else {
+            Err(RumbleError::Unsupported)
+        }
+    }
+}
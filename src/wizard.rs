@@ -0,0 +1,456 @@
+use ::event::{
+    Event,
+    EventData,
+};
+use ::gamepad::{
+    GamepadDescription,
+    GamepadState,
+};
+use ::mapping::{
+    Axis,
+    Button,
+};
+use ::sdl::{
+    ids_guid,
+    AxisHalf,
+    SdlBinding,
+    SdlInput,
+    SdlMapping,
+    SdlTarget,
+};
+use ::table::hat_mask;
+
+/// The longest an axis may take to move from rest past the threshold, in milliseconds.
+/// Slower movement is taken as drift.
+const MAX_MOVE_TIME: f64 = 500.0;
+
+/// The standard inputs asked for, in order.
+const STEPS: &[SdlTarget] = &[
+    SdlTarget::Button(Button::South),
+    SdlTarget::Button(Button::East),
+    SdlTarget::Button(Button::West),
+    SdlTarget::Button(Button::North),
+    SdlTarget::Button(Button::LT1),
+    SdlTarget::Button(Button::RT1),
+    SdlTarget::Button(Button::LT2),
+    SdlTarget::Button(Button::RT2),
+    SdlTarget::Button(Button::Select),
+    SdlTarget::Button(Button::Start),
+    SdlTarget::Button(Button::LeftStick),
+    SdlTarget::Button(Button::RightStick),
+    SdlTarget::Button(Button::Up),
+    SdlTarget::Button(Button::Down),
+    SdlTarget::Button(Button::Left),
+    SdlTarget::Button(Button::Right),
+    SdlTarget::Button(Button::Home),
+    SdlTarget::Axis(Axis::LeftStickX, None),
+    SdlTarget::Axis(Axis::LeftStickY, None),
+    SdlTarget::Axis(Axis::RightStickX, None),
+    SdlTarget::Axis(Axis::RightStickY, None),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AxisTrack {
+    rest: f64,
+    /// When the axis left rest, or `None` while near rest.
+    moved_at: Option<f64>,
+}
+
+/// A captured input, which must be released before the next is captured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Held {
+    Button(usize),
+    /// (axis index, direction moved from rest)
+    Axis(usize, f64),
+}
+
+/// Do these inputs read the same button, axis range, or hat direction?
+fn overlaps(input: SdlInput, other: SdlInput) -> bool {
+    match (input, other) {
+        (SdlInput::Button(index), SdlInput::Button(other_index)) => index == other_index,
+        (SdlInput::Axis { index, half, .. }, SdlInput::Axis { index: other_index, half: other_half, .. }) =>
+            index == other_index && (half.is_none() || other_half.is_none() || half == other_half),
+        (SdlInput::Hat(index, mask), SdlInput::Hat(other_index, other_mask)) => index == other_index && mask == other_mask,
+        (SdlInput::Hat(index, _), SdlInput::Axis { index: other_index, .. })
+            | (SdlInput::Axis { index, .. }, SdlInput::Hat(other_index, _)) => index == other_index,
+        _ => false,
+    }
+}
+
+/// The input to capture for `target`, from an axis moved from `rest` to `value`.
+fn axis_input(target: SdlTarget, index: usize, rest: f64, value: f64) -> Option<SdlInput> {
+    let moved = value - rest;
+    match target {
+        // sticks rest at the center
+        SdlTarget::Axis(_, _) if rest.abs() < 0.5 =>
+            Some(SdlInput::Axis { index, half: None, inverted: moved < 0.0 }),
+        SdlTarget::Axis(_, _) => None,
+
+        // a hat switch, past 1.0 at rest, and only captured in one direction
+        SdlTarget::Button(_) if rest > 1.0 => {
+            let mask = hat_mask(value);
+            if mask.count_ones() == 1 { Some(SdlInput::Hat(index, mask)) } else { None }
+        },
+
+        // triggers rest at one end, and use the whole range
+        SdlTarget::Button(_) if rest <= -0.5 && moved > 0.0 =>
+            Some(SdlInput::Axis { index, half: None, inverted: false }),
+        SdlTarget::Button(_) if rest >= 0.5 && moved < 0.0 =>
+            Some(SdlInput::Axis { index, half: None, inverted: true }),
+
+        SdlTarget::Button(_) => {
+            let half = if moved > 0.0 { AxisHalf::Positive } else { AxisHalf::Negative };
+            Some(SdlInput::Axis { index, half: Some(half), inverted: false })
+        },
+    }
+}
+
+/// Builds a mapping for a gamepad without one, by asking the player to press each button and move each axis.
+///
+/// The wizard asks for each standard [Button](enum.Button.html) then [Axis](enum.Axis.html),
+/// and captures the raw button, axis, half axis, or hat direction which moved.
+/// Feed it the gamepad's raw events with [handle()](#method.handle),
+/// and show the player its [prompt()](#method.prompt).
+///
+/// To reject noise, an axis must move past the [threshold](#method.set_threshold) quickly;
+/// an axis drifting there slowly is ignored.
+/// Each captured input must be released before the next is captured,
+/// and inputs already captured are not captured again.
+/// A trigger which reads 0.0 until first pressed is captured as a half axis,
+/// then as its full axis once it is released to the other end.
+///
+/// Hat switches are read from axes, as browsers report them,
+/// so a captured [Hat](enum.SdlInput.html#variant.Hat) holds an axis index.
+/// Use the mapping as a [MappingTable](struct.MappingTable.html) to read them;
+/// hats in an [SdlDatabase](struct.SdlDatabase.html) are numbered by SDL, and not mapped.
+///
+/// ```no_run
+/// use gamepad_web::*;
+///
+/// let mut monitor = Monitor::new();
+/// # let index = 0;
+/// let mut wizard = MappingWizard::new(monitor.pad_description(index).unwrap(), monitor.pad_state(index).unwrap());
+///
+/// // each frame
+/// while let Some(event) = monitor.poll() {
+///     wizard.handle(&event);
+/// }
+/// match wizard.prompt() {
+///     Some(prompt) => println!("{}", prompt), // "press South"
+///     None => {
+///         // as a table, so hats are read from the browser's axes
+///         let table = MappingTable::from(wizard.mapping());
+///         let name = monitor.pad_description(index).unwrap().name.clone();
+///         monitor.set_mapping_resolver(move |desc| if desc.name == name { Some(table.clone()) } else { None });
+///     },
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MappingWizard {
+    gamepad: GamepadDescription,
+    axes: Vec<AxisTrack>,
+    /// The input captured for each step so far, or `None` if skipped.
+    captured: Vec<Option<SdlInput>>,
+    held: Option<Held>,
+    threshold: f64,
+}
+
+impl MappingWizard {
+
+    /// Start mapping a gamepad, in its current state, which is taken as the rest position of its axes.
+    pub fn new(gamepad: &GamepadDescription, state: &GamepadState) -> Self {
+        let axes = (0..state.axis_count())
+            .map(|i| AxisTrack {
+                rest: state.axis(i),
+                moved_at: None,
+            })
+            .collect();
+
+        Self {
+            gamepad: gamepad.clone(),
+            axes,
+            captured: vec![],
+            held: None,
+            threshold: 0.5,
+        }
+    }
+
+    /// Set how far an axis must move from rest to be captured. 0.5 by default.
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+
+    /// The standard input asked for, or `None` once every input is mapped or skipped.
+    pub fn current(&self) -> Option<SdlTarget> {
+        STEPS.get(self.captured.len()).cloned()
+    }
+
+    /// Instructions for the player, such as "press South" or "push LeftStickX right",
+    /// or `None` once finished.
+    pub fn prompt(&self) -> Option<String> {
+        self.current().map(|target| match target {
+            SdlTarget::Button(button) => format!("press {:?}", button),
+            SdlTarget::Axis(axis @ Axis::LeftStickX, _) | SdlTarget::Axis(axis @ Axis::RightStickX, _) =>
+                format!("push {:?} right", axis),
+            SdlTarget::Axis(axis, _) => format!("push {:?} down", axis),
+        })
+    }
+
+    /// The number of steps done, and the total number of steps.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.captured.len(), STEPS.len())
+    }
+
+    /// Is every input mapped or skipped?
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Leave the current input unmapped, and ask for the next.
+    pub fn skip(&mut self) {
+        if !self.is_finished() {
+            self.captured.push(None);
+        }
+    }
+
+    /// Forget the last input mapped or skipped, and ask for it again.
+    ///
+    /// Returns the input asked for, or `None` if nothing was done yet.
+    pub fn undo(&mut self) -> Option<SdlTarget> {
+        self.captured.pop()?;
+        self.current()
+    }
+
+    /// Handle a raw event, and return the binding it captured, if any.
+    ///
+    /// Events from other gamepads are ignored.
+    pub fn handle(&mut self, event: &Event) -> Option<SdlBinding> {
+        if event.gamepad.index != self.gamepad.index {
+            return None;
+        }
+
+        let (input, held) = match event.data {
+            EventData::Button(index, pressed) => self.button(index, pressed)?,
+            EventData::Axis(index, value) => self.axis(index, value, event.timestamp)?,
+            _ => return None,
+        };
+        let target = self.current()?;
+        if self.captured.iter().flatten().any(|&used| overlaps(used, input)) {
+            return None;
+        }
+
+        self.captured.push(Some(input));
+        self.held = Some(held);
+        Some(SdlBinding { target, input })
+    }
+
+    fn button(&mut self, index: usize, pressed: bool) -> Option<(SdlInput, Held)> {
+        if !pressed {
+            if self.held == Some(Held::Button(index)) {
+                self.held = None;
+            }
+            return None;
+        }
+
+        match (self.held, self.current()?) {
+            (None, SdlTarget::Button(_)) => Some((SdlInput::Button(index), Held::Button(index))),
+            _ => None,
+        }
+    }
+
+    fn axis(&mut self, index: usize, value: f64, timestamp: f64) -> Option<(SdlInput, Held)> {
+        if index >= self.axes.len() {
+            self.axes.resize(index + 1, AxisTrack { rest: 0.0, moved_at: None });
+        }
+        let threshold = self.threshold;
+        let track = &mut self.axes[index];
+        let moved = value - track.rest;
+
+        if moved.abs() < threshold / 2.0 {
+            track.moved_at = None;
+            if let Some(Held::Axis(held, _)) = self.held {
+                if held == index {
+                    self.held = None;
+                }
+            }
+            return None;
+        }
+
+        if let Some(held) = self.held {
+            // an axis which reported 0.0 until first moved, and now rests at the other end,
+            // so it was captured as a half axis, but is a trigger using the whole range
+            if let Held::Axis(held, direction) = held {
+                if held == index && moved * direction < 0.0 {
+                    track.rest = value;
+                    track.moved_at = None;
+                    self.held = None;
+                    if let Some(&mut Some(SdlInput::Axis { index: captured, ref mut half, ref mut inverted })) = self.captured.last_mut() {
+                        if captured == index && half.is_some() {
+                            *half = None;
+                            *inverted = direction < 0.0;
+                        }
+                    }
+                }
+            }
+            return None;
+        }
+
+        let moved_at = *track.moved_at.get_or_insert(timestamp);
+        if moved.abs() < threshold {
+            return None;
+        }
+        if timestamp - moved_at > MAX_MOVE_TIME {
+            // drifting, so take this as its new rest
+            track.rest = value;
+            track.moved_at = None;
+            return None;
+        }
+
+        let rest = track.rest;
+        let input = axis_input(self.current()?, index, rest, value)?;
+        Some((input, Held::Axis(index, moved.signum())))
+    }
+
+    /// The bindings captured so far.
+    pub fn bindings(&self) -> Vec<SdlBinding> {
+        STEPS.iter()
+            .zip(&self.captured)
+            .filter_map(|(&target, input)| input.map(|input| SdlBinding { target, input }))
+            .collect()
+    }
+
+    /// A mapping of the bindings captured so far.
+    ///
+    /// It has the gamepad's vendor and product IDs if known, so it can be added to an
+    /// [SdlDatabase](struct.SdlDatabase.html#method.add), or exported with `to_string()`.
+    pub fn mapping(&self) -> SdlMapping {
+        let identity = self.gamepad.identity();
+        let guid = identity.as_ref()
+            .map_or_else(|| "0".repeat(32), |identity| ids_guid(identity.vendor_id, identity.product_id));
+        let name = identity.map_or_else(|| self.gamepad.name.clone(), |identity| identity.product_name);
+
+        SdlMapping {
+            guid,
+            // commas separate the fields of SDL mappings
+            name: name.replace(',', " "),
+            platform: None,
+            bindings: self.bindings(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::gamepad::GamepadMappingType;
+    use ::mock::MockSource;
+    use ::monitor::Monitor;
+
+    const HAT_REST: f64 = 9.0 / 7.0;
+
+    /// Apply `input` to the pad, and return what the wizard captured.
+    fn script<F: FnOnce(&mut MockSource)>(monitor: &mut Monitor<MockSource>, wizard: &mut MappingWizard, input: F) -> Vec<SdlBinding> {
+        input(monitor.source_mut());
+        monitor.source_mut().bump_timestamp(0);
+        let mut captured = vec![];
+        while let Some(event) = monitor.poll() {
+            captured.extend(wizard.handle(&event));
+        }
+        captured
+    }
+
+    fn binding(target: SdlTarget, input: SdlInput) -> Vec<SdlBinding> {
+        vec![SdlBinding { target, input }]
+    }
+
+    #[test]
+    fn test_wizard() {
+        let mut monitor = Monitor::with_source(MockSource::new());
+        monitor.source_mut().connect(GamepadDescription::new(0, "1234-5678-Generic, Pad".into(), GamepadMappingType::NoMapping, 8, 12));
+        // a trigger resting at -1.0, a hat switch, and a trigger reporting 0.0 until first pressed
+        monitor.source_mut().set_axis(0, 2, -1.0);
+        monitor.source_mut().set_axis(0, 5, HAT_REST);
+        monitor.source_mut().bump_timestamp(0);
+        while monitor.poll().is_some() {}
+        let mut wizard = MappingWizard::new(monitor.pad_description(0).unwrap(), monitor.pad_state(0).unwrap());
+        let m = &mut monitor;
+        let w = &mut wizard;
+
+        assert_eq!(w.prompt(), Some("press South".into()));
+        assert_eq!(script(m, w, |pad| pad.press(0, 0)), binding(SdlTarget::Button(Button::South), SdlInput::Button(0)));
+
+        // wait for release
+        assert_eq!(script(m, w, |pad| pad.press(0, 1)), vec![]);
+        script(m, w, |pad| { pad.release(0, 0); pad.release(0, 1); });
+        assert_eq!(script(m, w, |pad| pad.press(0, 1)), binding(SdlTarget::Button(Button::East), SdlInput::Button(1)));
+        script(m, w, |pad| pad.release(0, 1));
+
+        // already mapped, then skip and undo
+        assert_eq!(script(m, w, |pad| pad.press(0, 0)), vec![]);
+        script(m, w, |pad| pad.release(0, 0));
+        w.skip();
+        assert_eq!(w.current(), Some(SdlTarget::Button(Button::North)));
+        assert_eq!(w.undo(), Some(SdlTarget::Button(Button::West)));
+        for i in 2..6 {
+            script(m, w, |pad| pad.press(0, i));
+            script(m, w, |pad| pad.release(0, i));
+        }
+
+        // triggers
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 2, 1.0)),
+            binding(SdlTarget::Button(Button::LT2), SdlInput::Axis { index: 2, half: None, inverted: false }));
+        script(m, w, |pad| pad.set_axis(0, 2, -1.0));
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 6, 1.0)),
+            binding(SdlTarget::Button(Button::RT2), SdlInput::Axis { index: 6, half: Some(AxisHalf::Positive), inverted: false }));
+        // released to -1.0, so it uses the whole axis
+        script(m, w, |pad| pad.set_axis(0, 6, -1.0));
+        assert_eq!(w.bindings().last(), Some(&SdlBinding {
+            target: SdlTarget::Button(Button::RT2),
+            input: SdlInput::Axis { index: 6, half: None, inverted: false },
+        }));
+
+        for i in 6..10 {
+            script(m, w, |pad| pad.press(0, i));
+            script(m, w, |pad| pad.release(0, i));
+        }
+
+        // the hat switch, with diagonals rejected
+        assert_eq!(w.prompt(), Some("press Up".into()));
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 5, -5.0 / 7.0)), vec![]);
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 5, -1.0)), binding(SdlTarget::Button(Button::Up), SdlInput::Hat(5, 1)));
+        script(m, w, |pad| pad.set_axis(0, 5, HAT_REST));
+        w.skip();
+        w.skip();
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 5, -1.0 / 7.0)), vec![]);
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 5, -3.0 / 7.0)), binding(SdlTarget::Button(Button::Right), SdlInput::Hat(5, 2)));
+        script(m, w, |pad| pad.set_axis(0, 5, HAT_REST));
+
+        // a slowly drifting axis is ignored
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 7, 0.3)), vec![]);
+        let timestamp = m.pad_state(0).unwrap().timestamp();
+        assert_eq!(script(m, w, |pad| { pad.set_axis(0, 7, 0.6); pad.set_timestamp(0, timestamp + 1000.0); }), vec![]);
+        assert_eq!(script(m, w, |pad| pad.press(0, 11)), binding(SdlTarget::Button(Button::Home), SdlInput::Button(11)));
+        script(m, w, |pad| pad.release(0, 11));
+
+        // sticks
+        assert_eq!(w.prompt(), Some("push LeftStickX right".into()));
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 0, 0.9)),
+            binding(SdlTarget::Axis(Axis::LeftStickX, None), SdlInput::Axis { index: 0, half: None, inverted: false }));
+        script(m, w, |pad| pad.set_axis(0, 0, 0.0));
+        assert_eq!(w.prompt(), Some("push LeftStickY down".into()));
+        assert_eq!(script(m, w, |pad| pad.set_axis(0, 1, -0.9)),
+            binding(SdlTarget::Axis(Axis::LeftStickY, None), SdlInput::Axis { index: 1, half: None, inverted: true }));
+        w.skip();
+        w.skip();
+        assert!(w.is_finished());
+        assert_eq!(w.progress(), (21, 21));
+        assert_eq!(w.prompt(), None);
+
+        let mapping = w.mapping();
+        assert_eq!(mapping.ids(), Some((0x1234, 0x5678)));
+        assert_eq!(mapping.to_string(), "03000000341200007856000000000000,Generic  Pad,\
+            a:b0,b:b1,x:b2,y:b3,leftshoulder:b4,rightshoulder:b5,lefttrigger:a2,righttrigger:a6,\
+            back:b6,start:b7,leftstick:b8,rightstick:b9,dpup:h5.1,dpright:h5.2,guide:b11,leftx:a0,lefty:a1~,");
+        assert_eq!(mapping.to_string().parse::<SdlMapping>(), Ok(mapping));
+    }
+}